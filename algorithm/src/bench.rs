@@ -0,0 +1,543 @@
+//! ソートアルゴリズムの実行時間と比較・交換回数を計測する。
+
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::metrics::{self, Presortedness};
+use crate::sort::{
+    Sorter,
+    generator::{Distribution, Generator},
+    registry,
+};
+
+/// 計測の設定
+#[derive(Debug)]
+pub struct BenchConfig {
+    /// `i32` の入力で計測するアルゴリズム
+    pub algorithms: Vec<Box<dyn Sorter<i32>>>,
+    /// `String` の入力で計測するアルゴリズム
+    pub string_algorithms: Vec<Box<dyn Sorter<String>>>,
+    /// [0, 1) の範囲の `f64` の入力で計測するアルゴリズム
+    pub float_algorithms: Vec<Box<dyn Sorter<f64>>>,
+    pub sizes: Vec<usize>,
+    pub distributions: Vec<Distribution>,
+    /// 計測する回数
+    pub repetitions: usize,
+    /// 計測前に結果を捨てて実行する回数
+    pub warmup: usize,
+    /// 入力を作る [`Generator`] のシード
+    pub seed: u64,
+}
+
+impl Default for BenchConfig {
+    /// 全てのアルゴリズムを、長さ 1000 と 10000 のランダムな入力で5回ずつ計測する。
+    /// 文字列の入力では、文字列に特化したソートと `quick`・`merge` を比べる。
+    /// `f64` の入力では、バケットソートと `f64` のソートを含む全てのアルゴリズムを比べる。
+    /// シードは毎回ランダムに選ぶ。
+    fn default() -> Self {
+        Self {
+            algorithms: registry::integer_sorts(),
+            string_algorithms: [
+                "quick",
+                "merge",
+                "multikey-quick",
+                "msd-string",
+                "lcp-merge",
+            ]
+            .into_iter()
+            .filter_map(registry::find_string)
+            .collect(),
+            float_algorithms: registry::unit_interval_sorts(),
+            sizes: vec![1_000, 10_000],
+            distributions: vec![Distribution::Random],
+            repetitions: 5,
+            warmup: 1,
+            seed: rand::random(),
+        }
+    }
+}
+
+/// 1つのアルゴリズム・分布・サイズの組に対する計測結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchResult {
+    pub algorithm: &'static str,
+    /// 入力の要素の型（`i32`、`string` または `f64`）
+    pub element: &'static str,
+    pub distribution: Distribution,
+    pub size: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration,
+    pub compare_count: usize,
+    pub swap_count: usize,
+    /// 入力の乱れ具合
+    pub disorder: Presortedness,
+}
+
+/// 設定の全ての組み合わせについて計測する。
+///
+/// 入力は分布・サイズ・要素の型ごとに `seed` から作り直した [`Generator`] で1つ作り、各アルゴリズムの各回でその複製をソートする。
+/// 文字列の入力は、整数の入力と同じ並びを順序を保って文字列にしたもの。
+/// `f64` の入力は、整数の入力を順序を保って [0, 1) の範囲に縮めたもの。
+/// そのため、同じシード・分布・サイズの入力は、他の設定によらず同じになる。
+/// 時間は記録なしの [`Sorter::sort`] で測り、比較・交換回数は計測の外で別に1回 [`Sorter::sort_counted`] を実行して数える。
+/// 入力の乱れ具合（[`Presortedness`]）も入力ごとに1回だけ求め、結果に含める。
+/// [`Sorter::max_len`] を超えるサイズは、そのアルゴリズムでは計測しない。
+///
+/// # Errors
+/// ソート結果が昇順になっていなければエラーを返す。
+pub fn run(config: &BenchConfig) -> Result<Vec<BenchResult>, String> {
+    let mut results = Vec::new();
+
+    for &distribution in &config.distributions {
+        for &size in &config.sizes {
+            let input = Generator::new(config.seed).generate(distribution, size);
+            let disorder = metrics::measure(&input);
+
+            for algorithm in &config.algorithms {
+                if exceeds_max_len(algorithm.as_ref(), size) {
+                    continue;
+                }
+                let mut result = measure(algorithm.as_ref(), "i32", distribution, &input, config)?;
+                result.disorder = disorder;
+                results.push(result);
+            }
+
+            let floats = unit_interval(&input);
+            for algorithm in &config.float_algorithms {
+                if exceeds_max_len(algorithm.as_ref(), size) {
+                    continue;
+                }
+                let mut result = measure(algorithm.as_ref(), "f64", distribution, &floats, config)?;
+                result.disorder = disorder;
+                results.push(result);
+            }
+
+            if config.string_algorithms.is_empty() {
+                continue;
+            }
+            let input = Generator::new(config.seed).generate::<String>(distribution, size);
+            for algorithm in &config.string_algorithms {
+                if exceeds_max_len(algorithm.as_ref(), size) {
+                    continue;
+                }
+                let mut result =
+                    measure(algorithm.as_ref(), "string", distribution, &input, config)?;
+                result.disorder = disorder;
+                results.push(result);
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+/// 最小値が 0、最大値が 1 未満になるように、順序を保って `input` を [0, 1) の範囲に縮める。
+fn unit_interval(input: &[i32]) -> Vec<f64> {
+    let (Some(&min), Some(&max)) = (input.iter().min(), input.iter().max()) else {
+        return Vec::new();
+    };
+    let range = (max as i64 - min as i64 + 1) as f64;
+    input
+        .iter()
+        .map(|&x| (x as i64 - min as i64) as f64 / range)
+        .collect()
+}
+
+/// `size` が `algorithm` の [`Sorter::max_len`] を超えるか
+fn exceeds_max_len<T>(algorithm: &dyn Sorter<T>, size: usize) -> bool {
+    algorithm.max_len().is_some_and(|max_len| size > max_len)
+}
+
+fn measure<T: PartialOrd + Clone>(
+    algorithm: &dyn Sorter<T>,
+    element: &'static str,
+    distribution: Distribution,
+    input: &[T],
+    config: &BenchConfig,
+) -> Result<BenchResult, String> {
+    for _ in 0..config.warmup {
+        algorithm.sort(&mut input.to_vec());
+    }
+
+    let is_sorted = |v: &[T]| v.windows(2).all(|w| w[0] <= w[1]);
+    let unsorted = || {
+        format!(
+            "{} did not sort {distribution} {element} input of size {}",
+            algorithm.name(),
+            input.len()
+        )
+    };
+
+    // 計測するのは記録なしのソートだけで、比較・交換回数は計測の外で1回だけ数える
+    let mut times = Vec::with_capacity(config.repetitions);
+    for _ in 0..config.repetitions.max(1) {
+        let mut v = input.to_vec();
+        let now = Instant::now();
+        algorithm.sort(&mut v);
+        times.push(now.elapsed());
+
+        if !is_sorted(&v) {
+            return Err(unsorted());
+        }
+    }
+
+    let mut v = input.to_vec();
+    let result = algorithm.sort_counted(&mut v);
+    if !is_sorted(result.sorted) {
+        return Err(unsorted());
+    }
+    let (compare_count, swap_count) = (result.compare_count, result.swap_count);
+
+    let (min, median, mean, stddev) = summarize(&mut times);
+    Ok(BenchResult {
+        algorithm: algorithm.name(),
+        element,
+        distribution,
+        size: input.len(),
+        min,
+        median,
+        mean,
+        stddev,
+        compare_count,
+        swap_count,
+        disorder: Presortedness::default(),
+    })
+}
+
+/// 実行時間の (最小値, 中央値, 平均値, 標準偏差) を求める。
+fn summarize(times: &mut [Duration]) -> (Duration, Duration, Duration, Duration) {
+    times.sort();
+    let n = times.len();
+
+    let median = if n % 2 == 1 {
+        times[n / 2]
+    } else {
+        (times[n / 2 - 1] + times[n / 2]) / 2
+    };
+
+    let secs = times.iter().map(Duration::as_secs_f64);
+    let mean = secs.clone().sum::<f64>() / n as f64;
+    let variance = secs.map(|t| (t - mean).powi(2)).sum::<f64>() / n as f64;
+
+    (
+        times[0],
+        median,
+        Duration::from_secs_f64(mean),
+        Duration::from_secs_f64(variance.sqrt()),
+    )
+}
+
+/// 計測結果の出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// 桁を揃えた表
+    Table,
+    Csv,
+    /// オブジェクトの配列
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Self::Table),
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("unknown format: {s}")),
+        }
+    }
+}
+
+const COLUMNS: [&str; 14] = [
+    "algorithm",
+    "element",
+    "distribution",
+    "size",
+    "min",
+    "median",
+    "mean",
+    "stddev",
+    "compares",
+    "swaps",
+    "inversions",
+    "runs",
+    "rem",
+    "displacement",
+];
+
+/// 計測結果を `format` の形式で `w` に書き出す。
+///
+/// 表では時間を読みやすい単位で、CSV と JSON ではナノ秒の整数で出力する。
+pub fn write_results(
+    w: &mut impl Write,
+    results: &[BenchResult],
+    format: Format,
+) -> io::Result<()> {
+    match format {
+        Format::Table => write_table(w, results),
+        Format::Csv => write_csv(w, results),
+        Format::Json => write_json(w, results),
+    }
+}
+
+fn write_table(w: &mut impl Write, results: &[BenchResult]) -> io::Result<()> {
+    let rows = results
+        .iter()
+        .map(|r| {
+            [
+                r.algorithm.to_string(),
+                r.element.to_string(),
+                r.distribution.to_string(),
+                r.size.to_string(),
+                format!("{:.3?}", r.min),
+                format!("{:.3?}", r.median),
+                format!("{:.3?}", r.mean),
+                format!("{:.3?}", r.stddev),
+                r.compare_count.to_string(),
+                r.swap_count.to_string(),
+                r.disorder.inversions.to_string(),
+                r.disorder.runs.to_string(),
+                r.disorder.rem.to_string(),
+                r.disorder.displacement.to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let widths = (0..COLUMNS.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].len())
+                .chain([COLUMNS[i].len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<_>>();
+
+    let header = COLUMNS.map(String::from);
+    for row in std::iter::once(&header).chain(&rows) {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            // 名前の列は左寄せ、数値の列は右寄せ
+            .map(|(i, (cell, &width))| {
+                if i < 3 {
+                    format!("{cell:<width$}")
+                } else {
+                    format!("{cell:>width$}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        writeln!(w, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+fn write_csv(w: &mut impl Write, results: &[BenchResult]) -> io::Result<()> {
+    writeln!(
+        w,
+        "algorithm,element,distribution,size,min_ns,median_ns,mean_ns,stddev_ns,compares,swaps,inversions,runs,rem,displacement"
+    )?;
+    for r in results {
+        writeln!(
+            w,
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            r.algorithm,
+            r.element,
+            r.distribution,
+            r.size,
+            r.min.as_nanos(),
+            r.median.as_nanos(),
+            r.mean.as_nanos(),
+            r.stddev.as_nanos(),
+            r.compare_count,
+            r.swap_count,
+            r.disorder.inversions,
+            r.disorder.runs,
+            r.disorder.rem,
+            r.disorder.displacement
+        )?;
+    }
+    Ok(())
+}
+
+fn write_json(w: &mut impl Write, results: &[BenchResult]) -> io::Result<()> {
+    writeln!(w, "[")?;
+    for (i, r) in results.iter().enumerate() {
+        let separator = if i + 1 < results.len() { "," } else { "" };
+        writeln!(
+            w,
+            r#"  {{"algorithm": "{}", "element": "{}", "distribution": "{}", "size": {}, "min_ns": {}, "median_ns": {}, "mean_ns": {}, "stddev_ns": {}, "compares": {}, "swaps": {}, "inversions": {}, "runs": {}, "rem": {}, "displacement": {}}}{separator}"#,
+            r.algorithm,
+            r.element,
+            r.distribution,
+            r.size,
+            r.min.as_nanos(),
+            r.median.as_nanos(),
+            r.mean.as_nanos(),
+            r.stddev.as_nanos(),
+            r.compare_count,
+            r.swap_count,
+            r.disorder.inversions,
+            r.disorder.runs,
+            r.disorder.rem,
+            r.disorder.displacement
+        )?;
+    }
+    writeln!(w, "]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(algorithms: &[&str]) -> BenchConfig {
+        BenchConfig {
+            algorithms: algorithms
+                .iter()
+                .map(|name| registry::find(name).unwrap())
+                .collect(),
+            string_algorithms: Vec::new(),
+            float_algorithms: Vec::new(),
+            sizes: vec![10, 100],
+            distributions: Distribution::ALL.to_vec(),
+            repetitions: 3,
+            warmup: 1,
+            seed: 0,
+        }
+    }
+
+    #[test]
+    fn test_all_algorithms_sort() {
+        let names = registry::integer_sorts()
+            .iter()
+            .map(|sorter| sorter.name())
+            .collect::<Vec<_>>();
+        let results = run(&config(&names)).unwrap();
+        assert_eq!(results.len(), names.len() * 2 * Distribution::ALL.len());
+    }
+
+    #[test]
+    fn test_string_inputs() {
+        let mut config = config(&["quick"]);
+        config.string_algorithms = registry::string_sorts();
+        let results = run(&config).unwrap();
+
+        let per_input = 1 + registry::string_sorts().len();
+        assert_eq!(results.len(), per_input * 2 * Distribution::ALL.len());
+        assert_eq!(results[0].element, "i32");
+        assert!(results[1..per_input].iter().all(|r| r.element == "string"));
+        // 文字列は整数と同じ並びなので、乱れ具合も同じになる
+        assert!(
+            results[1..per_input]
+                .iter()
+                .all(|r| r.disorder == results[0].disorder)
+        );
+    }
+
+    #[test]
+    fn test_float_inputs() {
+        let mut config = config(&["quick"]);
+        config.float_algorithms = ["quick", "heap", "bucket", "float"]
+            .into_iter()
+            .filter_map(registry::find_float)
+            .collect();
+        let results = run(&config).unwrap();
+
+        assert_eq!(results.len(), 5 * 2 * Distribution::ALL.len());
+        let names = results[1..5]
+            .iter()
+            .map(|r| r.algorithm)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["quick", "heap", "bucket", "float"]);
+        assert!(results[1..5].iter().all(|r| r.element == "f64"));
+        // 浮動小数点数も整数と同じ並びなので、比較ソートの比較回数は整数の入力と同じになる
+        assert_eq!(results[1].compare_count, results[0].compare_count);
+    }
+
+    #[test]
+    fn test_unit_interval() {
+        assert_eq!(unit_interval(&[]), []);
+        assert_eq!(
+            unit_interval(&[3, 1, 2, 1]),
+            [2.0 / 3.0, 0.0, 1.0 / 3.0, 0.0]
+        );
+        let v = unit_interval(&[i32::MAX, i32::MIN]);
+        assert!(v[0] < 1.0 && v[1] == 0.0);
+    }
+
+    #[test]
+    fn test_summarize() {
+        let mut times = [3, 1, 4, 1, 5].map(Duration::from_millis);
+        let (min, median, mean, stddev) = summarize(&mut times);
+        assert_eq!(min, Duration::from_millis(1));
+        assert_eq!(median, Duration::from_millis(3));
+        assert_eq!(mean.as_micros(), 2800);
+        // 分散 = (0.04 + 3.24 + 3.24 + 1.44 + 4.84) / 5 = 2.56 (ms^2)
+        assert_eq!(stddev.as_micros(), 1600);
+    }
+
+    #[test]
+    fn test_reproducible_counts() {
+        let counts = |results: Vec<BenchResult>| {
+            results
+                .into_iter()
+                .map(|r| (r.compare_count, r.swap_count))
+                .collect::<Vec<_>>()
+        };
+        let a = run(&config(&["quick", "tim"])).unwrap();
+        let b = run(&config(&["quick", "tim"])).unwrap();
+        assert_eq!(counts(a), counts(b));
+    }
+
+    #[test]
+    fn test_disorder() {
+        let results = run(&config(&["insertion"])).unwrap();
+        for r in &results {
+            match r.distribution {
+                Distribution::Sorted => assert_eq!(r.disorder.inversions, 0),
+                Distribution::Reversed => {
+                    assert_eq!(r.disorder.inversions, r.size * (r.size - 1) / 2)
+                }
+                _ => {}
+            }
+            // 挿入ソートの交換回数（書き込み回数）は転倒数に比例する
+            assert!(r.swap_count >= r.disorder.inversions);
+        }
+    }
+
+    #[test]
+    fn test_write_results() {
+        let results = run(&config(&["merge"])).unwrap();
+
+        let mut csv = Vec::new();
+        write_results(&mut csv, &results, Format::Csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().count(), results.len() + 1);
+        assert!(
+            csv.lines()
+                .nth(1)
+                .unwrap()
+                .starts_with("merge,i32,random,10,")
+        );
+
+        let mut json = Vec::new();
+        write_results(&mut json, &results, Format::Json).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        assert!(json.starts_with("[\n  {\"algorithm\": \"merge\""));
+        assert!(json.trim_end().ends_with("}\n]"));
+
+        let mut table = Vec::new();
+        write_results(&mut table, &results, Format::Table).unwrap();
+        let table = String::from_utf8(table).unwrap();
+        let header = table.lines().next().unwrap();
+        assert_eq!(header.split_whitespace().collect::<Vec<_>>(), COLUMNS);
+        assert_eq!(table.lines().count(), results.len() + 1);
+    }
+}
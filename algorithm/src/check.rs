@@ -0,0 +1,176 @@
+//! シードを指定して再現できる、ランダムな入力による差分テストの道具。
+//!
+//! 入力を作って性質（多くは素朴な実装との一致）を確かめ、成り立たない入力が見つかったら、
+//! 成り立たないままでいられる限り入力を小さくしていき（縮小）、最小の入力を報告する。
+//! シードは環境変数 `CHECK_SEED` で、試行回数は `CHECK_CASES` で変えられる。
+
+use std::env;
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// `CHECK_SEED` がないときのシード
+const DEFAULT_SEED: u64 = 20_240_601;
+
+/// `CHECK_CASES` がないときの試行回数
+const DEFAULT_CASES: usize = 200;
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+/// `generate` で作った入力に対して `property` が成り立つことを確かめる。
+///
+/// `property` がパニックした場合も成り立たなかったものとして扱う。
+///
+/// # Panics
+/// 成り立たない入力があれば、`shrink` で縮小した最小の入力とシードを示してパニックする。
+pub fn check<T, G, S, P>(name: &str, mut generate: G, shrink: S, property: P)
+where
+    T: Clone + Debug,
+    G: FnMut(&mut StdRng) -> T,
+    S: Fn(&T) -> Vec<T>,
+    P: Fn(&T) -> Result<(), String>,
+{
+    let seed = env_or("CHECK_SEED", DEFAULT_SEED);
+    let cases = env_or("CHECK_CASES", DEFAULT_CASES);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let run = |input: &T| {
+        panic::catch_unwind(AssertUnwindSafe(|| property(input))).unwrap_or_else(|payload| {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            Err(format!("panicked: {message}"))
+        })
+    };
+
+    for case in 0..cases {
+        let input = generate(&mut rng);
+        let Err(message) = run(&input) else {
+            continue;
+        };
+
+        // 縮小した候補のうち、最初に失敗したものに移ることを、失敗する候補がなくなるまで繰り返す
+        let (mut minimal, mut message) = (input.clone(), message);
+        while let Some((smaller, smaller_message)) = shrink(&minimal)
+            .into_iter()
+            .find_map(|candidate| run(&candidate).err().map(|m| (candidate, m)))
+        {
+            minimal = smaller;
+            message = smaller_message;
+        }
+
+        panic!(
+            "{name}: case {case} failed (CHECK_SEED={seed})\n  input:   {input:?}\n  minimal: {minimal:?}\n  {message}"
+        );
+    }
+}
+
+/// `expected` と `actual` が等しければ `Ok`、異なれば両方を示すエラー。
+pub fn equal<T: PartialEq + Debug>(expected: T, actual: T) -> Result<(), String> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(format!("expected {expected:?}, got {actual:?}"))
+    }
+}
+
+/// 長さ `0..=max_len` の整数の列。半分は値の範囲を狭めて、重複の多い列にする。
+///
+/// 境界を確実に試すため、1/8 の確率で空か1要素の列にする。
+pub fn vec_i32(rng: &mut StdRng, max_len: usize) -> Vec<i32> {
+    let len = if rng.random_ratio(1, 8) {
+        rng.random_range(0..=max_len.min(1))
+    } else {
+        rng.random_range(0..=max_len)
+    };
+    let range = if rng.random() { 1_000 } else { 4 };
+    (0..len).map(|_| rng.random_range(-range..range)).collect()
+}
+
+/// 列を縮小した候補を、小さいものから順に返す。
+///
+/// 1. 連続する len/2, len/4, ..., 1 個の要素を取り除いたもの
+/// 2. 1つの要素を `shrink_elem` で小さくしたもの
+pub fn shrink_vec<T: Clone>(v: &[T], shrink_elem: impl Fn(&T) -> Vec<T>) -> Vec<Vec<T>> {
+    let mut candidates = Vec::new();
+
+    let mut chunk = v.len().div_ceil(2);
+    while chunk > 0 {
+        for start in (0..v.len()).step_by(chunk) {
+            let end = (start + chunk).min(v.len());
+            candidates.push([&v[..start], &v[end..]].concat());
+        }
+        chunk /= 2;
+    }
+
+    for (i, elem) in v.iter().enumerate() {
+        for smaller in shrink_elem(elem) {
+            let mut w = v.to_vec();
+            w[i] = smaller;
+            candidates.push(w);
+        }
+    }
+    candidates
+}
+
+/// 0 に近づけた整数の候補
+pub fn shrink_i32(x: &i32) -> Vec<i32> {
+    let mut candidates = vec![0, x / 2, x - x.signum()];
+    candidates.retain(|c| c != x);
+    candidates.dedup();
+    candidates
+}
+
+/// 0 に近づけた自然数の候補
+pub fn shrink_usize(x: &usize) -> Vec<usize> {
+    let mut candidates = vec![0, x / 2, x.saturating_sub(1)];
+    candidates.retain(|c| c != x);
+    candidates.dedup();
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shrinks_to_minimal_case() {
+        // 3 以上の要素を含む列で失敗する性質は、[3] まで縮小される
+        let result = panic::catch_unwind(|| {
+            check(
+                "no large elements",
+                |rng| vec_i32(rng, 20),
+                |v| shrink_vec(v, shrink_i32),
+                |v| match v.iter().find(|&&x| x >= 3) {
+                    Some(x) => Err(format!("found {x}")),
+                    None => Ok(()),
+                },
+            )
+        });
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.contains("minimal: [3]\n"), "{message}");
+        assert!(message.contains("CHECK_SEED="), "{message}");
+    }
+
+    #[test]
+    fn test_passing_property() {
+        check(
+            "sorted after sort",
+            |rng| vec_i32(rng, 20),
+            |v| shrink_vec(v, shrink_i32),
+            |v| {
+                let mut w = v.clone();
+                w.sort();
+                equal(true, w.windows(2).all(|w| w[0] <= w[1]))
+            },
+        );
+    }
+}
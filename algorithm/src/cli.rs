@@ -0,0 +1,490 @@
+//! コマンドライン引数の解釈とサブコマンドの実行。
+
+use std::error::Error;
+use std::io;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::bench::{self, BenchConfig, Format};
+use crate::indefinite_equation;
+use crate::sort::generator::{Distribution, Generator};
+use crate::sort::trace::{self, AnimateConfig, TracedSort};
+use crate::sort::{Sorter, registry};
+
+/// 使い方の説明
+pub const USAGE: &str = "\
+usage: algorithm <command> [options]
+
+commands:
+  bench   ソートアルゴリズムの実行時間と比較・交換回数を計測する
+      --algorithms <name,...>     整数の入力で計測するアルゴリズム (既定: 全て)
+      --strings <name,...|none>   文字列の入力で計測するアルゴリズム
+                                  (既定: quick,merge,multikey-quick,msd-string,lcp-merge)
+      --floats <name,...|none>    [0, 1) の浮動小数点数の入力で計測するアルゴリズム (既定: 全て)
+      --sizes <n,...>             入力の長さ (既定: 1000,10000)
+      --distributions <name,...>  入力の分布 (既定: random)
+      --reps <n>                  計測する回数 (既定: 5)
+      --warmup <n>                計測前に捨てる実行の回数 (既定: 1)
+      --seed <n>                  入力を作る乱数のシード (既定: ランダム)
+      --format <table|csv|json>   出力形式 (既定: table)
+  solve   不定方程式 coeffs · x = target の解を、分散の小さい順に表示する
+      --target <n>                右辺 (既定: 278790)
+      --coeffs <n,...>            係数 (既定: 669,596,485,403,361)
+      --factor-count <n>          全ての解が持つべき素因数の個数の下限 (既定: 5)
+  trace   ソート中の比較・交換・代入を記録し、棒グラフのアニメーションで再生する
+      --algorithm <name>          bubble, shaker, quick, heap, insertion, merge (既定: quick)
+      --size <n>                  入力の長さ (既定: 32)
+      --distribution <name>       入力の分布 (既定: random)
+      --seed <n>                  入力を作る乱数のシード (既定: ランダム)
+      --format <animate|jsonl>    再生するか、JSON Lines で書き出すか (既定: animate)
+      --delay <ms>                1コマの表示時間 (既定: 50)
+      --height <n>                棒グラフの高さ (既定: 16)
+      --skip-compares <bool>      比較だけのコマを飛ばす (既定: false)
+  list    計測できるアルゴリズムと分布の一覧を表示する
+  help    この説明を表示する";
+
+/// 実行するサブコマンド
+#[derive(Debug)]
+pub enum Command {
+    Bench(BenchConfig, Format),
+    Solve(SolveConfig),
+    Trace(TraceConfig),
+    List,
+    Help,
+}
+
+/// `solve` の設定
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolveConfig {
+    pub target: usize,
+    pub coeffs: Vec<usize>,
+    pub factor_count: usize,
+}
+
+impl Default for SolveConfig {
+    /// `669a + 596b + 485c + 403d + 361e = 278790` の、素因数を5個以上持つ解を求める。
+    fn default() -> Self {
+        Self {
+            target: 278_790,
+            coeffs: vec![669, 596, 485, 403, 361],
+            factor_count: 5,
+        }
+    }
+}
+
+/// `trace` の出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceFormat {
+    Animate,
+    JsonLines,
+}
+
+/// `trace` の設定
+#[derive(Debug, Clone)]
+pub struct TraceConfig {
+    pub algorithm: (&'static str, TracedSort),
+    pub size: usize,
+    pub distribution: Distribution,
+    pub seed: u64,
+    pub format: TraceFormat,
+    pub animate: AnimateConfig,
+}
+
+impl Default for TraceConfig {
+    /// クイックソートで、長さ 32 のランダムな入力を再生する。シードは毎回ランダムに選ぶ。
+    fn default() -> Self {
+        Self {
+            algorithm: trace::ALGORITHMS[2],
+            size: 32,
+            distribution: Distribution::Random,
+            seed: rand::random(),
+            format: TraceFormat::Animate,
+            animate: AnimateConfig::default(),
+        }
+    }
+}
+
+impl Command {
+    /// プログラム名を除いたコマンドライン引数からサブコマンドを作る。
+    ///
+    /// # Errors
+    /// 未知のサブコマンド・オプションや、解釈できない値があればエラーを返す。
+    pub fn build(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let command = match args.next() {
+            Some(command) => command,
+            None => return Ok(Self::Help),
+        };
+
+        match command.as_str() {
+            "bench" => build_bench(args),
+            "solve" => build_solve(args),
+            "trace" => build_trace(args),
+            "list" => Ok(Self::List),
+            "help" | "-h" | "--help" => Ok(Self::Help),
+            _ => Err(format!("unknown command: {command}")),
+        }
+    }
+}
+
+fn build_bench(args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut config = BenchConfig::default();
+    let mut format = Format::Table;
+
+    for (option, value) in options(args)? {
+        match option.as_str() {
+            "--algorithms" => {
+                config.algorithms = split(&value, |name| {
+                    registry::find(name).ok_or_else(|| format!("unknown algorithm: {name}"))
+                })?
+            }
+            "--strings" if value == "none" => config.string_algorithms = Vec::new(),
+            "--strings" => {
+                config.string_algorithms = split(&value, |name| {
+                    registry::find_string(name)
+                        .ok_or_else(|| format!("unknown string algorithm: {name}"))
+                })?
+            }
+            "--floats" if value == "none" => config.float_algorithms = Vec::new(),
+            "--floats" => {
+                config.float_algorithms = split(&value, |name| {
+                    registry::find_float(name)
+                        .ok_or_else(|| format!("unknown float algorithm: {name}"))
+                })?
+            }
+            "--sizes" => config.sizes = split(&value, parse_number)?,
+            "--distributions" => config.distributions = split(&value, str::parse)?,
+            "--reps" => config.repetitions = parse_number(&value)?,
+            "--warmup" => config.warmup = parse_number(&value)?,
+            "--seed" => config.seed = parse_number(&value)?,
+            "--format" => format = value.parse()?,
+            _ => return Err(format!("unknown option for bench: {option}")),
+        }
+    }
+
+    if config.repetitions == 0 {
+        return Err("--reps must be at least 1".to_string());
+    }
+    Ok(Command::Bench(config, format))
+}
+
+fn build_solve(args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut config = SolveConfig::default();
+
+    for (option, value) in options(args)? {
+        match option.as_str() {
+            "--target" => config.target = parse_number(&value)?,
+            "--coeffs" => config.coeffs = split(&value, parse_number)?,
+            "--factor-count" => config.factor_count = parse_number(&value)?,
+            _ => return Err(format!("unknown option for solve: {option}")),
+        }
+    }
+
+    if config.coeffs.is_empty() || config.coeffs.contains(&0) {
+        return Err("--coeffs must be positive integers".to_string());
+    }
+    Ok(Command::Solve(config))
+}
+
+fn build_trace(args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut config = TraceConfig::default();
+
+    for (option, value) in options(args)? {
+        match option.as_str() {
+            "--algorithm" => {
+                config.algorithm = trace::ALGORITHMS
+                    .into_iter()
+                    .find(|(name, _)| *name == value)
+                    .ok_or_else(|| format!("unknown algorithm for trace: {value}"))?
+            }
+            "--size" => config.size = parse_number(&value)?,
+            "--distribution" => config.distribution = value.parse()?,
+            "--seed" => config.seed = parse_number(&value)?,
+            "--format" => {
+                config.format = match value.as_str() {
+                    "animate" => TraceFormat::Animate,
+                    "jsonl" => TraceFormat::JsonLines,
+                    _ => return Err(format!("unknown format for trace: {value}")),
+                }
+            }
+            "--delay" => config.animate.delay = Duration::from_millis(parse_number(&value)?),
+            "--height" => config.animate.height = parse_number(&value)?,
+            "--skip-compares" => {
+                config.animate.skip_compares = value
+                    .parse()
+                    .map_err(|_| format!("invalid bool: {value}"))?
+            }
+            _ => return Err(format!("unknown option for trace: {option}")),
+        }
+    }
+
+    Ok(Command::Trace(config))
+}
+
+/// 引数を `--option value` の組に分ける。
+fn options(mut args: impl Iterator<Item = String>) -> Result<Vec<(String, String)>, String> {
+    let mut options = Vec::new();
+    while let Some(option) = args.next() {
+        if !option.starts_with("--") {
+            return Err(format!("unexpected argument: {option}"));
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {option}"))?;
+        options.push((option, value));
+    }
+    Ok(options)
+}
+
+/// カンマ区切りの値をそれぞれ `parse` で解釈する。
+fn split<T, E>(value: &str, parse: impl Fn(&str) -> Result<T, E>) -> Result<Vec<T>, E> {
+    value.split(',').map(|s| parse(s.trim())).collect()
+}
+
+/// `10_000` のように `_` で区切った数も受け付ける。
+fn parse_number<T: FromStr>(value: &str) -> Result<T, String> {
+    value
+        .replace('_', "")
+        .parse()
+        .map_err(|_| format!("invalid number: {value}"))
+}
+
+/// サブコマンドを実行し、結果を標準出力に書き出す。
+///
+/// # Errors
+/// 計測でソート結果が正しくなかった場合や、出力に失敗した場合にエラーを返す。
+pub fn run(command: Command) -> Result<(), Box<dyn Error>> {
+    match command {
+        Command::Bench(config, format) => {
+            // 結果を再現できるように、使ったシードを残しておく
+            eprintln!("seed: {}", config.seed);
+            let results = bench::run(&config)?;
+            bench::write_results(&mut io::stdout().lock(), &results, format)?;
+        }
+        Command::Solve(config) => {
+            for (solve, variance) in solve(&config) {
+                println!("{solve:>3?} {}", variance.round());
+            }
+        }
+        Command::Trace(config) => {
+            eprintln!("seed: {}", config.seed);
+            let mut v = Generator::new(config.seed).generate(config.distribution, config.size);
+            let trace = (config.algorithm.1)(&mut v);
+
+            let mut stdout = io::stdout().lock();
+            match config.format {
+                TraceFormat::Animate => trace::animate(&trace, &mut stdout, &config.animate)?,
+                TraceFormat::JsonLines => trace.write_json_lines(&mut stdout)?,
+            }
+        }
+        Command::List => {
+            println!("algorithms:");
+            for sorter in registry::integer_sorts() {
+                print_sorter(sorter.as_ref());
+            }
+            println!("string algorithms (in addition to comparison sorts):");
+            for sorter in registry::string_sorts() {
+                if registry::find(sorter.name()).is_none() {
+                    print_sorter(sorter.as_ref());
+                }
+            }
+            println!("float algorithms (in addition to comparison sorts):");
+            for sorter in registry::unit_interval_sorts() {
+                if registry::find(sorter.name()).is_none() {
+                    print_sorter(sorter.as_ref());
+                }
+            }
+            println!("traceable algorithms:");
+            for (name, _) in trace::ALGORITHMS {
+                println!("  {name}");
+            }
+            println!("distributions:");
+            for distribution in Distribution::ALL {
+                println!("  {distribution}");
+            }
+        }
+        Command::Help => println!("{USAGE}"),
+    }
+    Ok(())
+}
+
+fn print_sorter<T>(sorter: &dyn Sorter<T>) {
+    let worst = sorter.worst().to_string();
+    let worst = match sorter.max_len() {
+        Some(max_len) => format!("{worst:<12}  max-len: {max_len}"),
+        None => worst,
+    };
+    println!(
+        "  {:<16} stable: {:<5}  in-place: {:<5}  average: {:<12}  worst: {worst}",
+        sorter.name(),
+        sorter.is_stable(),
+        sorter.is_in_place(),
+        sorter.average().to_string(),
+    );
+}
+
+/// 不定方程式を解き、全ての解の素因数が `factor_count` 個以上あるものを、解の分散の昇順に返す。
+pub fn solve(config: &SolveConfig) -> Vec<(Vec<usize>, f64)> {
+    let solutions = indefinite_equation::solve_asc(&config.coeffs, config.target);
+    // 解は target / (最小の係数) を超えない
+    let max_solve = config.target / config.coeffs.iter().min().unwrap();
+    let factor_counts = indefinite_equation::count_total_prime_factors(max_solve);
+
+    let mut solution_variances = solutions
+        .into_iter()
+        .map(|solves| {
+            let mean = solves.iter().map(|x| *x as f64).sum::<f64>() / solves.len() as f64;
+            let variance = solves
+                .iter()
+                .map(|x| (*x as f64 - mean).powi(2))
+                .sum::<f64>()
+                / solves.len() as f64;
+
+            (solves, variance)
+        })
+        // 全ての解の因数が factor_count 個以上ある解のみを抽出
+        .filter(|(solves, _)| {
+            solves
+                .iter()
+                .all(|&c| factor_counts[c] >= config.factor_count)
+        })
+        .collect::<Vec<_>>();
+
+    solution_variances.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+    solution_variances
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(args: &[&str]) -> Result<Command, String> {
+        Command::build(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_build_bench() {
+        let command = build(&[
+            "bench",
+            "--algorithms",
+            "quick,tim",
+            "--strings",
+            "lcp-merge",
+            "--floats",
+            "bucket,quick",
+            "--sizes",
+            "100,10_000",
+            "--distributions",
+            "nearly-sorted:5,gaussian",
+            "--reps",
+            "3",
+            "--warmup",
+            "0",
+            "--seed",
+            "42",
+            "--format",
+            "csv",
+        ])
+        .unwrap();
+
+        let Command::Bench(config, format) = command else {
+            panic!("expected bench: {command:?}");
+        };
+        let names = config
+            .algorithms
+            .iter()
+            .map(|a| a.name())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["quick", "tim"]);
+        assert_eq!(config.string_algorithms[0].name(), "lcp-merge");
+        assert_eq!(config.float_algorithms[0].name(), "bucket");
+        assert_eq!(config.sizes, [100, 10_000]);
+        assert_eq!(
+            config.distributions,
+            [
+                Distribution::NearlySorted { swaps: 5 },
+                Distribution::Gaussian
+            ]
+        );
+        assert_eq!((config.repetitions, config.warmup, config.seed), (3, 0, 42));
+        assert_eq!(format, Format::Csv);
+    }
+
+    #[test]
+    fn test_build_defaults() {
+        let Ok(Command::Bench(config, Format::Table)) = build(&["bench"]) else {
+            panic!("expected bench with table format");
+        };
+        assert_eq!(config.algorithms.len(), registry::integer_sorts().len());
+        assert_eq!(config.string_algorithms.len(), 5);
+        assert_eq!(
+            config.float_algorithms.len(),
+            registry::unit_interval_sorts().len()
+        );
+        let Ok(Command::Bench(config, _)) = build(&["bench", "--strings", "none"]) else {
+            panic!("expected bench");
+        };
+        assert!(config.string_algorithms.is_empty());
+        let Ok(Command::Bench(config, _)) = build(&["bench", "--floats", "none"]) else {
+            panic!("expected bench");
+        };
+        assert!(config.float_algorithms.is_empty());
+
+        let Ok(Command::Solve(config)) = build(&["solve", "--target", "1000"]) else {
+            panic!("expected solve");
+        };
+        assert_eq!(config.target, 1000);
+        assert_eq!(config.coeffs, SolveConfig::default().coeffs);
+
+        assert!(matches!(build(&[]), Ok(Command::Help)));
+        assert!(matches!(build(&["list"]), Ok(Command::List)));
+    }
+
+    #[test]
+    fn test_build_trace() {
+        let command = build(&[
+            "trace",
+            "--algorithm",
+            "heap",
+            "--size",
+            "10",
+            "--format",
+            "jsonl",
+            "--skip-compares",
+            "true",
+        ])
+        .unwrap();
+
+        let Command::Trace(config) = command else {
+            panic!("expected trace: {command:?}");
+        };
+        assert_eq!(config.algorithm.0, "heap");
+        assert_eq!(config.size, 10);
+        assert_eq!(config.format, TraceFormat::JsonLines);
+        assert!(config.animate.skip_compares);
+    }
+
+    #[test]
+    fn test_build_errors() {
+        assert!(build(&["sort"]).is_err());
+        assert!(build(&["bench", "--algorithms", "quick,bogo"]).is_err());
+        assert!(build(&["bench", "--strings", "radix-lsd"]).is_err());
+        assert!(build(&["bench", "--floats", "radix-lsd"]).is_err());
+        assert!(build(&["bench", "--sizes"]).is_err());
+        assert!(build(&["bench", "--reps", "0"]).is_err());
+        assert!(build(&["bench", "--format", "xml"]).is_err());
+        assert!(build(&["bench", "quick"]).is_err());
+        assert!(build(&["solve", "--coeffs", "3,0"]).is_err());
+        assert!(build(&["trace", "--algorithm", "tim"]).is_err());
+    }
+
+    #[test]
+    fn test_solve() {
+        // 5a + 3b = 30 の a <= b を満たす自然数解は (3, 5) のみ
+        let config = SolveConfig {
+            target: 30,
+            coeffs: vec![5, 3],
+            factor_count: 1,
+        };
+        assert_eq!(solve(&config), [(vec![3, 5], 1.0)]);
+    }
+}
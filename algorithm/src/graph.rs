@@ -1,31 +1,313 @@
-use std::cmp::Reverse;
-use std::collections::BinaryHeap;
+//! 有向・無向、重み付き・重みなしのグラフと、グラフのアルゴリズム。
+//!
+//! グラフは [`GraphBuilder`] でノードと辺を追加して作る。ノードは `0..node_count()` の番号で表し、
+//! 必要ならラベル（名前）を付けて、ラベルから番号を引ける。
+//! 作ったグラフは変更できず、辺は CSR（Compressed Sparse Row）形式で、ノードごとに連続した配列に並べて持つ。
 
-pub fn dijkstra(
-    graph: &[Vec<(usize, u32)>], // 隣接リスト: 各ノードに (to, weight) のリスト
-    start: usize,
-) -> Vec<u32> {
-    let mut dist = vec![u32::MAX; graph.len()];
-    let mut heap = BinaryHeap::new();
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
 
-    dist[start] = 0;
-    heap.push(Reverse((0, start))); // (距離, ノード)
+use weight::Weight;
 
-    while let Some(Reverse((d, u))) = heap.pop() {
-        if d > dist[u] {
-            continue; // 古い距離のエントリはスキップ
+/// 2次元のグリッド上の経路探索
+pub mod grid;
+/// 最短経路
+pub mod shortest_path;
+/// 辺の重みの型
+pub mod weight;
+
+/// 辺の向き
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// 辺 (u, v) は u から v へだけ進める
+    Directed,
+    /// 辺 (u, v) は u と v のどちらからも進める
+    Undirected,
+}
+
+/// グラフを作れなかった理由
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError {
+    /// 存在しない番号のノードを指定した
+    NodeOutOfRange {
+        /// 指定したノードの番号
+        node: usize,
+        /// ノードの個数
+        node_count: usize,
+    },
+    /// 同じラベルを複数のノードに付けた
+    DuplicateLabel(String),
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NodeOutOfRange { node, node_count } => {
+                write!(f, "node {node} is out of range (node count: {node_count})")
+            }
+            Self::DuplicateLabel(label) => write!(f, "duplicate node label: {label}"),
         }
+    }
+}
+
+impl Error for GraphError {}
 
-        for &(v, w) in &graph[u] {
-            let next_dist = d + w;
-            if next_dist < dist[v] {
-                dist[v] = next_dist;
-                heap.push(Reverse((next_dist, v)));
+/// 変更できないグラフ
+///
+/// ノード u から出る辺は `targets[offsets[u]..offsets[u + 1]]` に、追加した順に並ぶ。
+/// 無向グラフの辺 (u, v) は、u から v と v から u の2本の有向辺として持つ（自己ループは1本）。
+/// 重みなしのグラフは重みを持たず、全ての辺の重みを [`Weight::one`] として扱う。
+#[derive(Debug, Clone)]
+pub struct Graph<W = u32> {
+    direction: Direction,
+    weighted: bool,
+    edge_count: usize,
+    offsets: Vec<usize>,
+    targets: Vec<usize>,
+    /// 重みなしのグラフでは空
+    weights: Vec<W>,
+    /// ラベルのないグラフでは空
+    labels: Vec<Option<String>>,
+    indices: HashMap<String, usize>,
+    /// 重みが負の最初の辺。作るときに1回だけ調べておく。
+    negative_edge: Option<(usize, usize, W)>,
+}
+
+impl<W: Weight> Graph<W> {
+    /// 隣接リスト（各ノードから出る `(to, weight)` のリスト）から重み付きの有向グラフを作る。
+    ///
+    /// # Errors
+    /// 辺の行き先が存在しないノードの場合はエラーを返す。
+    pub fn from_adjacency(adjacency: &[Vec<(usize, W)>]) -> Result<Self, GraphError> {
+        let mut builder = GraphBuilder::with_weights(Direction::Directed, adjacency.len());
+        for (u, edges) in adjacency.iter().enumerate() {
+            for &(v, w) in edges {
+                builder.weighted_edge(u, v, w);
             }
         }
+        builder.build()
+    }
+
+    /// 辺の向き
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// 有向グラフか
+    pub fn is_directed(&self) -> bool {
+        self.direction == Direction::Directed
+    }
+
+    /// 重み付きのグラフか
+    pub fn is_weighted(&self) -> bool {
+        self.weighted
+    }
+
+    /// ノードの個数
+    pub fn node_count(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    /// 追加した辺の本数。無向グラフの辺も1本と数える。
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
     }
 
-    dist
+    /// ノード `u` から出る辺の本数（無向グラフでは次数）
+    ///
+    /// # Panics
+    /// `u` が存在しないノードの場合
+    pub fn degree(&self, u: usize) -> usize {
+        self.offsets[u + 1] - self.offsets[u]
+    }
+
+    /// ノード `u` から出る辺の `(行き先, 重み)` を、追加した順に返す。
+    ///
+    /// # Panics
+    /// `u` が存在しないノードの場合
+    pub fn neighbors(&self, u: usize) -> impl Iterator<Item = (usize, W)> + '_ {
+        let range = self.offsets[u]..self.offsets[u + 1];
+        range.map(|i| (self.targets[i], self.weight(i)))
+    }
+
+    /// 全ての有向辺の `(元, 行き先, 重み)`。無向グラフの辺は両方向の2本として現れる。
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize, W)> + '_ {
+        (0..self.node_count()).flat_map(move |u| self.neighbors(u).map(move |(v, w)| (u, v, w)))
+    }
+
+    /// 重みが負の辺があれば、[`edges`](Self::edges) の順で最初の辺の `(元, 行き先, 重み)` を返す。
+    /// グラフを作るときに求めておくため、O(1) で返る。
+    pub fn negative_edge(&self) -> Option<(usize, usize, W)> {
+        self.negative_edge
+    }
+
+    /// ノード `u` のラベル
+    pub fn label(&self, u: usize) -> Option<&str> {
+        self.labels.get(u)?.as_deref()
+    }
+
+    /// ラベルが `label` のノードの番号
+    pub fn index(&self, label: &str) -> Option<usize> {
+        self.indices.get(label).copied()
+    }
+
+    /// `u` が存在するノードか確かめる。
+    ///
+    /// # Errors
+    /// `u` が存在しないノードの場合はエラーを返す。
+    pub fn check_node(&self, u: usize) -> Result<(), GraphError> {
+        check_node(u, self.node_count())
+    }
+
+    fn weight(&self, i: usize) -> W {
+        self.weights.get(i).copied().unwrap_or_else(W::one)
+    }
+}
+
+fn check_node(node: usize, node_count: usize) -> Result<(), GraphError> {
+    if node < node_count {
+        Ok(())
+    } else {
+        Err(GraphError::NodeOutOfRange { node, node_count })
+    }
+}
+
+/// ノードと辺を追加して [`Graph`] を作る。
+///
+/// 追加するときには確かめず、[`GraphBuilder::build`] でまとめて確かめる。
+/// 重みを指定した辺を1本でも追加すると重み付きのグラフになり、重みを指定しない辺の重みは [`Weight::one`] になる。
+#[derive(Debug, Clone)]
+pub struct GraphBuilder<W = u32> {
+    direction: Direction,
+    node_count: usize,
+    edges: Vec<(usize, usize, W)>,
+    weighted: bool,
+    labels: Vec<(usize, String)>,
+}
+
+impl GraphBuilder {
+    /// `node_count` 個のノードを持ち、辺のないグラフから始める。重みは `u32`。
+    pub fn new(direction: Direction, node_count: usize) -> Self {
+        Self::with_weights(direction, node_count)
+    }
+}
+
+impl<W: Weight> GraphBuilder<W> {
+    /// [`GraphBuilder::new`] と同じだが、重みの型を `GraphBuilder::<u64>::with_weights(..)` のように指定する。
+    pub fn with_weights(direction: Direction, node_count: usize) -> Self {
+        Self {
+            direction,
+            node_count,
+            edges: Vec::new(),
+            weighted: false,
+            labels: Vec::new(),
+        }
+    }
+
+    /// ラベル `label` のノードを追加し、その番号を返す。
+    pub fn add_node(&mut self, label: &str) -> usize {
+        let u = self.node_count;
+        self.node_count += 1;
+        self.labels.push((u, label.to_string()));
+        u
+    }
+
+    /// ノード `u` にラベル `label` を付ける。既にラベルがあれば付け替える（同じラベルを付け直してもよい）。
+    pub fn label(&mut self, u: usize, label: &str) -> &mut Self {
+        self.labels.push((u, label.to_string()));
+        self
+    }
+
+    /// 重みなしの辺 (u, v) を追加する。
+    pub fn edge(&mut self, u: usize, v: usize) -> &mut Self {
+        self.edges.push((u, v, W::one()));
+        self
+    }
+
+    /// 重み `w` の辺 (u, v) を追加する。
+    pub fn weighted_edge(&mut self, u: usize, v: usize, w: W) -> &mut Self {
+        self.edges.push((u, v, w));
+        self.weighted = true;
+        self
+    }
+
+    /// ノードと辺を確かめ、CSR 形式のグラフを作る。
+    ///
+    /// 1. 各ノードから出る辺の本数を数え、その累積和を各ノードの辺の開始位置とする。
+    /// 2. 辺を追加した順に、元のノードの開始位置から詰めて置く。
+    ///
+    /// # Errors
+    /// 辺の端点やラベルを付けたノードが存在しない場合と、同じラベルを複数のノードに付けた場合はエラーを返す。
+    pub fn build(&self) -> Result<Graph<W>, GraphError> {
+        let n = self.node_count;
+
+        let mut labels = Vec::new();
+        let mut indices = HashMap::new();
+        if !self.labels.is_empty() {
+            labels = vec![None; n];
+            for (u, label) in &self.labels {
+                check_node(*u, n)?;
+                if let Some(other) = indices.insert(label.clone(), *u)
+                    && other != *u
+                {
+                    return Err(GraphError::DuplicateLabel(label.clone()));
+                }
+                if let Some(old) = labels[*u].replace(label.clone())
+                    && old != *label
+                {
+                    indices.remove(&old);
+                }
+            }
+        }
+
+        for &(u, v, _) in &self.edges {
+            check_node(u, n)?;
+            check_node(v, n)?;
+        }
+
+        // 無向グラフの辺は両方向に置く
+        let undirected = self.direction == Direction::Undirected;
+        let arcs = self.edges.iter().flat_map(|&(u, v, w)| {
+            let reverse = (undirected && u != v).then_some((v, u, w));
+            [Some((u, v, w)), reverse].into_iter().flatten()
+        });
+
+        let mut offsets = vec![0; n + 1];
+        for (u, _, _) in arcs.clone() {
+            offsets[u + 1] += 1;
+        }
+        for u in 0..n {
+            offsets[u + 1] += offsets[u];
+        }
+
+        let m = offsets[n];
+        let mut next = offsets.clone();
+        let mut targets = vec![0; m];
+        let mut weights = vec![W::zero(); if self.weighted { m } else { 0 }];
+        for (u, v, w) in arcs {
+            targets[next[u]] = v;
+            if self.weighted {
+                weights[next[u]] = w;
+            }
+            next[u] += 1;
+        }
+
+        let mut graph = Graph {
+            direction: self.direction,
+            weighted: self.weighted,
+            edge_count: self.edges.len(),
+            offsets,
+            targets,
+            weights,
+            labels,
+            indices,
+            negative_edge: None,
+        };
+        let negative_edge = graph.edges().find(|&(_, _, w)| w < W::zero());
+        graph.negative_edge = negative_edge;
+        Ok(graph)
+    }
 }
 
 #[cfg(test)]
@@ -33,14 +315,115 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_dijkstra() {
-        let graph = vec![
-            vec![(1, 1), (2, 4)],
-            vec![(0, 1), (2, 2), (3, 5)],
-            vec![(0, 4), (1, 2), (3, 1)],
-            vec![(1, 5), (2, 1)],
-        ];
-        let dist = dijkstra(&graph, 0);
-        assert_eq!(dist, vec![0, 1, 3, 4]);
+    fn test_directed_graph() {
+        let graph = GraphBuilder::new(Direction::Directed, 3)
+            .weighted_edge(0, 1, 5)
+            .weighted_edge(0, 2, 3)
+            .weighted_edge(2, 1, 1)
+            .build()
+            .unwrap();
+        assert!(graph.is_directed() && graph.is_weighted());
+        assert_eq!((graph.node_count(), graph.edge_count()), (3, 3));
+        assert_eq!(graph.neighbors(0).collect::<Vec<_>>(), [(1, 5), (2, 3)]);
+        assert_eq!(graph.neighbors(1).count(), 0);
+        assert_eq!(
+            graph.edges().collect::<Vec<_>>(),
+            [(0, 1, 5), (0, 2, 3), (2, 1, 1)]
+        );
+    }
+
+    #[test]
+    fn test_undirected_unweighted_graph() {
+        let graph = GraphBuilder::new(Direction::Undirected, 4)
+            .edge(0, 1)
+            .edge(1, 2)
+            .edge(3, 3)
+            .build()
+            .unwrap();
+        assert!(!graph.is_directed() && !graph.is_weighted());
+        assert_eq!(graph.edge_count(), 3);
+        assert_eq!(graph.neighbors(1).collect::<Vec<_>>(), [(0, 1), (2, 1)]);
+        assert_eq!(graph.degree(2), 1);
+        // 自己ループは1本
+        assert_eq!(graph.neighbors(3).collect::<Vec<_>>(), [(3, 1)]);
+    }
+
+    #[test]
+    fn test_labels() {
+        let mut builder = GraphBuilder::new(Direction::Undirected, 0);
+        let tokyo = builder.add_node("Tokyo");
+        let osaka = builder.add_node("Osaka");
+        builder.weighted_edge(tokyo, osaka, 500);
+        let graph = builder.build().unwrap();
+
+        assert_eq!(graph.index("Osaka"), Some(osaka));
+        assert_eq!(graph.label(tokyo), Some("Tokyo"));
+        assert_eq!(graph.index("Nagoya"), None);
+
+        // ラベルのないグラフ
+        let graph = GraphBuilder::new(Direction::Directed, 2).build().unwrap();
+        assert_eq!(graph.label(0), None);
+    }
+
+    #[test]
+    fn test_relabel() {
+        // 同じノードに同じラベルを付け直してもよい
+        let graph = GraphBuilder::new(Direction::Directed, 2)
+            .label(0, "a")
+            .label(0, "a")
+            .build()
+            .unwrap();
+        assert_eq!(graph.index("a"), Some(0));
+        assert_eq!(graph.label(0), Some("a"));
+
+        // 付け替えると古いラベルでは引けず、古いラベルを他のノードに付けられる
+        let graph = GraphBuilder::new(Direction::Directed, 2)
+            .label(0, "a")
+            .label(0, "b")
+            .label(1, "a")
+            .build()
+            .unwrap();
+        assert_eq!(graph.index("b"), Some(0));
+        assert_eq!(graph.index("a"), Some(1));
+    }
+
+    #[test]
+    fn test_invalid_graph() {
+        let error = GraphBuilder::new(Direction::Directed, 2)
+            .edge(0, 2)
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            error,
+            GraphError::NodeOutOfRange {
+                node: 2,
+                node_count: 2
+            }
+        );
+        assert_eq!(error.to_string(), "node 2 is out of range (node count: 2)");
+
+        let mut builder = GraphBuilder::new(Direction::Directed, 2);
+        builder.label(0, "a").label(1, "a");
+        assert_eq!(
+            builder.build().unwrap_err(),
+            GraphError::DuplicateLabel("a".to_string())
+        );
+        assert!(
+            GraphBuilder::new(Direction::Directed, 1)
+                .label(1, "a")
+                .build()
+                .is_err()
+        );
+
+        assert!(Graph::from_adjacency(&[vec![(1, 1)]]).is_err());
+    }
+
+    #[test]
+    fn test_from_adjacency() {
+        let adjacency = vec![vec![(1, 1), (2, 4)], vec![(2, 2)], vec![]];
+        let graph = Graph::from_adjacency(&adjacency).unwrap();
+        for (u, edges) in adjacency.iter().enumerate() {
+            assert_eq!(&graph.neighbors(u).collect::<Vec<_>>(), edges);
+        }
     }
 }
@@ -0,0 +1,490 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use super::{Direction, Graph, GraphBuilder};
+
+/// 上下左右に1マス進むコスト
+pub const STRAIGHT_COST: u32 = 10;
+
+/// 斜めに1マス進むコスト（10√2 の近似）
+pub const DIAGONAL_COST: u32 = 14;
+
+/// ASCII の地図から作る2次元のグリッド
+///
+/// 地図は1行が1列のマスに対応し、`.` が通れるマス、`#` が壁を表す。
+/// `S` と `G` は通れるマスで、それぞれ始点と目的地の位置を表す（省略可）。
+/// マス (row, col) はグラフのノード `row * width + col` になり、壁のマスは辺のないノードになる。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid {
+    width: usize,
+    height: usize,
+    open: Vec<bool>,
+    start: Option<(usize, usize)>,
+    goal: Option<(usize, usize)>,
+}
+
+/// 地図を読めなかった理由
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridError {
+    /// 行の長さが1行目と異なる
+    UnevenRow {
+        /// 行の番号
+        row: usize,
+        /// 行の長さ
+        len: usize,
+        /// 1行目の長さ
+        width: usize,
+    },
+    /// `.`, `#`, `S`, `G` 以外の文字がある
+    UnknownCell {
+        /// 行の番号
+        row: usize,
+        /// 列の番号
+        col: usize,
+        /// その文字
+        cell: char,
+    },
+    /// `S` か `G` が複数ある
+    DuplicateMarker(char),
+    /// 行がないか、1行目が空で、幅が 0 になる
+    Empty,
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnevenRow { row, len, width } => {
+                write!(f, "row {row} has {len} cells, expected {width}")
+            }
+            Self::UnknownCell { row, col, cell } => {
+                write!(f, "unknown cell {cell:?} at ({row}, {col})")
+            }
+            Self::DuplicateMarker(marker) => write!(f, "duplicate marker {marker:?}"),
+            Self::Empty => write!(f, "map has no cells"),
+        }
+    }
+}
+
+impl Error for GridError {}
+
+impl FromStr for Grid {
+    type Err = GridError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows = s.lines().map(|line| line.trim_end_matches('\r'));
+        let mut grid = Grid {
+            width: 0,
+            height: 0,
+            open: Vec::new(),
+            start: None,
+            goal: None,
+        };
+
+        for (row, line) in rows.enumerate() {
+            let len = line.chars().count();
+            if row == 0 {
+                grid.width = len;
+            } else if len != grid.width {
+                let width = grid.width;
+                return Err(GridError::UnevenRow { row, len, width });
+            }
+
+            for (col, cell) in line.chars().enumerate() {
+                let marker = match cell {
+                    '.' | '#' => None,
+                    'S' => Some(&mut grid.start),
+                    'G' => Some(&mut grid.goal),
+                    _ => return Err(GridError::UnknownCell { row, col, cell }),
+                };
+                if let Some(marker) = marker
+                    && marker.replace((row, col)).is_some()
+                {
+                    return Err(GridError::DuplicateMarker(cell));
+                }
+                grid.open.push(cell != '#');
+            }
+            grid.height += 1;
+        }
+
+        // 幅が 0 だとマスの位置を求められない
+        if grid.width == 0 {
+            return Err(GridError::Empty);
+        }
+        Ok(grid)
+    }
+}
+
+/// 1回に進めるマス
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Moves {
+    /// 上下左右の4マス
+    Four,
+    /// 上下左右と斜めの8マス。斜めに進むときは、角を挟む上下左右の2マスがともに通れる必要がある。
+    Eight,
+}
+
+/// 2つのマスの間の距離の推定値
+///
+/// 壁がなければ、[`Heuristic::Manhattan`] は4マス移動、[`Heuristic::Octile`] は8マス移動の実際の距離に等しい。
+/// 実際の距離を超えない（許容的な）組み合わせは次のとおり。
+///
+/// | 移動 | Manhattan | Chebyshev | Octile |
+/// |------|-----------|-----------|--------|
+/// | 4マス | 許容的 | 許容的 | 許容的 |
+/// | 8マス | 許容的でない | 許容的 | 許容的 |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Heuristic {
+    /// 縦と横の差の和
+    Manhattan,
+    /// 縦と横の差の大きい方
+    Chebyshev,
+    /// 斜めに進めるだけ進み、残りを縦か横に進む距離
+    Octile,
+}
+
+impl Heuristic {
+    /// マス `from` から `to` への距離の推定値
+    pub fn estimate(self, from: (usize, usize), to: (usize, usize)) -> u32 {
+        // u64 で計算し、u32 で表せない推定値は u32::MAX にする（実際の距離を超えないので許容的なまま）
+        let dy = from.0.abs_diff(to.0) as u64;
+        let dx = from.1.abs_diff(to.1) as u64;
+        let (short, long) = (dx.min(dy), dx.max(dy));
+        let (straight, diagonal) = (u64::from(STRAIGHT_COST), u64::from(DIAGONAL_COST));
+        let estimate = match self {
+            Self::Manhattan => straight.saturating_mul(dx + dy),
+            Self::Chebyshev => straight.saturating_mul(long),
+            Self::Octile => diagonal
+                .saturating_mul(short)
+                .saturating_add(straight.saturating_mul(long - short)),
+        };
+        u32::try_from(estimate).unwrap_or(u32::MAX)
+    }
+}
+
+impl Grid {
+    /// 列の数
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// 行の数
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// 地図の `S` の位置
+    pub fn start(&self) -> Option<(usize, usize)> {
+        self.start
+    }
+
+    /// 地図の `G` の位置
+    pub fn goal(&self) -> Option<(usize, usize)> {
+        self.goal
+    }
+
+    /// マス (row, col) が通れるか。地図の外は通れない。
+    pub fn is_open(&self, row: usize, col: usize) -> bool {
+        row < self.height && col < self.width && self.open[row * self.width + col]
+    }
+
+    /// マス (row, col) のノードの番号
+    pub fn node(&self, (row, col): (usize, usize)) -> usize {
+        row * self.width + col
+    }
+
+    /// ノード `u` のマスの位置
+    pub fn position(&self, u: usize) -> (usize, usize) {
+        (u / self.width, u % self.width)
+    }
+
+    /// 通れるマスの間を `moves` で移動するグラフを作る。
+    ///
+    /// 辺の重みは上下左右が [`STRAIGHT_COST`]、斜めが [`DIAGONAL_COST`]。
+    pub fn to_graph(&self, moves: Moves) -> Graph {
+        let steps: &[(isize, isize)] = match moves {
+            Moves::Four => &[(-1, 0), (0, -1), (0, 1), (1, 0)],
+            Moves::Eight => &[
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, -1),
+                (0, 1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+            ],
+        };
+
+        let mut builder = GraphBuilder::new(Direction::Directed, self.width * self.height);
+        for row in 0..self.height {
+            for col in 0..self.width {
+                if !self.is_open(row, col) {
+                    continue;
+                }
+                for &(dy, dx) in steps {
+                    let (Some(r), Some(c)) =
+                        (row.checked_add_signed(dy), col.checked_add_signed(dx))
+                    else {
+                        continue;
+                    };
+                    let diagonal = dy != 0 && dx != 0;
+                    // 斜めの移動で壁の角をすり抜けない
+                    if !self.is_open(r, c)
+                        || diagonal && !(self.is_open(row, c) && self.is_open(r, col))
+                    {
+                        continue;
+                    }
+                    let cost = if diagonal {
+                        DIAGONAL_COST
+                    } else {
+                        STRAIGHT_COST
+                    };
+                    builder.weighted_edge(self.node((row, col)), self.node((r, c)), cost);
+                }
+            }
+        }
+        builder.build().expect("grid neighbours are in range")
+    }
+
+    /// ノードから `goal` への距離を `heuristic` で推定する関数。[`astar`](super::shortest_path::astar) に渡す。
+    pub fn heuristic(&self, heuristic: Heuristic, goal: (usize, usize)) -> impl Fn(usize) -> u32 {
+        let width = self.width;
+        move |u| heuristic.estimate((u / width, u % width), goal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::check::{self, check, shrink_vec};
+    use crate::graph::shortest_path::{astar, dijkstra_to};
+
+    const MAZE: &str = "\
+S....#....
+.###.#.##.
+.#...#..#.
+.#.###.##.
+.#.....#..
+.#####.#.#
+.......#.G
+";
+
+    #[test]
+    fn test_parse() {
+        let grid = MAZE.parse::<Grid>().unwrap();
+        assert_eq!((grid.width(), grid.height()), (10, 7));
+        assert_eq!((grid.start(), grid.goal()), (Some((0, 0)), Some((6, 9))));
+        assert!(grid.is_open(0, 4) && !grid.is_open(0, 5) && !grid.is_open(7, 0));
+        assert_eq!(grid.position(grid.node((3, 4))), (3, 4));
+
+        // CRLF の改行も読める
+        assert_eq!("S.\r\n.G\r\n".parse::<Grid>().unwrap().goal(), Some((1, 1)));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "...\n..\n".parse::<Grid>(),
+            Err(GridError::UnevenRow {
+                row: 1,
+                len: 2,
+                width: 3
+            })
+        );
+        let error = "..\n.x\n".parse::<Grid>().unwrap_err();
+        assert_eq!(error.to_string(), "unknown cell 'x' at (1, 1)");
+        assert_eq!(
+            "S.S\n".parse::<Grid>(),
+            Err(GridError::DuplicateMarker('S'))
+        );
+        assert_eq!("".parse::<Grid>(), Err(GridError::Empty));
+        assert_eq!("\n\n".parse::<Grid>(), Err(GridError::Empty));
+    }
+
+    #[test]
+    fn test_to_graph() {
+        let grid = "..\n.#\n".parse::<Grid>().unwrap();
+        let graph = grid.to_graph(Moves::Four);
+        assert_eq!(graph.neighbors(0).collect::<Vec<_>>(), [(1, 10), (2, 10)]);
+        assert_eq!(graph.degree(3), 0);
+
+        // 壁の角をすり抜ける斜めの移動はできない
+        let graph = grid.to_graph(Moves::Eight);
+        assert_eq!(graph.degree(1), 1);
+        let graph = "..\n..\n".parse::<Grid>().unwrap().to_graph(Moves::Eight);
+        assert_eq!(
+            graph.neighbors(1).collect::<Vec<_>>(),
+            [(0, 10), (2, 14), (3, 10)]
+        );
+    }
+
+    #[test]
+    fn test_heuristics() {
+        let (from, to) = ((0, 0), (2, 5));
+        assert_eq!(Heuristic::Manhattan.estimate(from, to), 70);
+        assert_eq!(Heuristic::Chebyshev.estimate(from, to), 50);
+        assert_eq!(Heuristic::Octile.estimate(from, to), 58);
+
+        // u32 に収まらない推定値は切り捨てずに u32::MAX にする
+        let far = (0, usize::MAX);
+        assert_eq!(Heuristic::Manhattan.estimate(from, far), u32::MAX);
+        assert_eq!(Heuristic::Octile.estimate(far, (usize::MAX, 0)), u32::MAX);
+    }
+
+    #[test]
+    fn test_astar_maze() {
+        let grid = MAZE.parse::<Grid>().unwrap();
+        let (start, goal) = (grid.node(grid.start().unwrap()), grid.goal().unwrap());
+        let cases = [
+            (Moves::Four, Heuristic::Manhattan),
+            (Moves::Eight, Heuristic::Octile),
+        ];
+        for (moves, heuristic) in cases {
+            let graph = grid.to_graph(moves);
+            let expected = dijkstra_to(&graph, start, grid.node(goal));
+            let dijkstra = astar(&graph, start, grid.node(goal), |_| 0);
+            let search = astar(
+                &graph,
+                start,
+                grid.node(goal),
+                grid.heuristic(heuristic, goal),
+            );
+            // 距離の等しい経路が複数あるので、距離だけを比べる
+            let cost = |path: &Option<(u32, Vec<usize>)>| path.as_ref().map(|(cost, _)| *cost);
+            assert_eq!(cost(&search.path), cost(&expected), "{moves:?}");
+            assert_eq!(cost(&dijkstra.path), cost(&expected));
+            // 迷路では A* もほとんどのマスを展開する
+            assert!(
+                search.expanded <= dijkstra.expanded,
+                "{search:?} {dijkstra:?}"
+            );
+        }
+
+        // 4マス移動の最短経路は 29 歩
+        let graph = grid.to_graph(Moves::Four);
+        let search = astar(
+            &graph,
+            start,
+            grid.node(goal),
+            grid.heuristic(Heuristic::Manhattan, goal),
+        );
+        let (cost, path) = search.path.unwrap();
+        assert_eq!((cost, path.len()), (290, 30));
+    }
+
+    #[test]
+    fn test_astar_open_map() {
+        // 壁のない地図では、ダイクストラ法は目的地より近いマスを全て展開する
+        let grid = format!("{}\n", ".".repeat(20))
+            .repeat(20)
+            .parse::<Grid>()
+            .unwrap();
+        let graph = grid.to_graph(Moves::Eight);
+        let (start, goal) = (grid.node((0, 0)), (19, 10));
+        let search = astar(
+            &graph,
+            start,
+            grid.node(goal),
+            grid.heuristic(Heuristic::Octile, goal),
+        );
+        let dijkstra = astar(&graph, start, grid.node(goal), |_| 0);
+        let cost = |path: &Option<(u32, Vec<usize>)>| path.as_ref().map(|(cost, _)| *cost);
+        assert_eq!(cost(&search.path), Some(230));
+        assert_eq!(cost(&dijkstra.path), Some(230));
+        assert!(dijkstra.expanded > 300, "{}", dijkstra.expanded);
+        // 推定値が正確なので、A* は最短経路の近くのマスだけを展開する
+        // （正確な数は推定値が等しいマスの取り出し順による）
+        assert!(search.expanded <= 40, "{}", search.expanded);
+    }
+
+    #[test]
+    fn test_unreachable_goal() {
+        let grid = "S#.\n.#G\n".parse::<Grid>().unwrap();
+        let graph = grid.to_graph(Moves::Eight);
+        let goal = grid.goal().unwrap();
+        let search = astar(
+            &graph,
+            0,
+            grid.node(goal),
+            grid.heuristic(Heuristic::Octile, goal),
+        );
+        assert_eq!(search.path, None);
+        assert_eq!(search.expanded, 2);
+    }
+
+    #[test]
+    fn test_differential_astar() {
+        // (高さ, 幅, 壁のマス, 始点, 目的地)。始点と目的地は壁にしない
+        type Map = (usize, usize, Vec<usize>, usize, usize);
+        let generate = |rng: &mut rand::rngs::StdRng| -> Map {
+            let (height, width) = (rng.random_range(1..=8), rng.random_range(1..=8));
+            let start = rng.random_range(0..height * width);
+            let goal = rng.random_range(0..height * width);
+            let walls = (0..height * width)
+                .filter(|&u| u != start && u != goal && rng.random_ratio(1, 3))
+                .collect();
+            (height, width, walls, start, goal)
+        };
+        let shrink = |(height, width, walls, start, goal): &Map| {
+            shrink_vec(walls, |_| Vec::new())
+                .into_iter()
+                .map(|walls| (*height, *width, walls, *start, *goal))
+                .collect()
+        };
+
+        check(
+            "astar",
+            generate,
+            shrink,
+            |(height, width, walls, start, goal)| {
+                let map = (0..*height)
+                    .map(|row| {
+                        (0..*width)
+                            .map(|col| {
+                                if walls.contains(&(row * width + col)) {
+                                    '#'
+                                } else {
+                                    '.'
+                                }
+                            })
+                            .collect::<String>()
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let grid = map.parse::<Grid>().map_err(|e| e.to_string())?;
+                let goal_position = grid.position(*goal);
+
+                let admissible = [
+                    (Moves::Four, Heuristic::Manhattan),
+                    (Moves::Four, Heuristic::Chebyshev),
+                    (Moves::Four, Heuristic::Octile),
+                    (Moves::Eight, Heuristic::Chebyshev),
+                    (Moves::Eight, Heuristic::Octile),
+                ];
+                for (moves, heuristic) in admissible {
+                    let graph = grid.to_graph(moves);
+                    let expected = dijkstra_to(&graph, *start, *goal).map(|(cost, _)| cost);
+                    let search = astar(
+                        &graph,
+                        *start,
+                        *goal,
+                        grid.heuristic(heuristic, goal_position),
+                    );
+                    let dijkstra = astar(&graph, *start, *goal, |_| 0);
+                    check::equal(expected, search.path.as_ref().map(|(cost, _)| *cost))
+                        .map_err(|e| format!("{moves:?} {heuristic:?}: {e}"))?;
+                    if search.expanded > dijkstra.expanded {
+                        return Err(format!(
+                            "{moves:?} {heuristic:?}: expanded {} > {}",
+                            search.expanded, dijkstra.expanded
+                        ));
+                    }
+                }
+                Ok(())
+            },
+        );
+    }
+}
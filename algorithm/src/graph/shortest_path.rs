@@ -0,0 +1,849 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::error::Error;
+use std::fmt;
+
+use super::Graph;
+use super::weight::Weight;
+
+/// 始点から各ノードへの最短経路（最短経路木）
+///
+/// 各ノードの最短距離と、最短経路で1つ前のノード（親）を持つ。親を始点までたどると最短経路になる。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortestPaths<W = u32> {
+    start: usize,
+    dist: Vec<Option<W>>,
+    pred: Vec<Option<usize>>,
+}
+
+impl<W: Weight> ShortestPaths<W> {
+    /// 始点
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// 全てのノードの最短距離。到達できないノードは `None`。
+    pub fn distances(&self) -> &[Option<W>] {
+        &self.dist
+    }
+
+    /// ノード `v` への最短距離。到達できなければ `None`。
+    ///
+    /// # Panics
+    /// `v` が存在しないノードの場合
+    pub fn distance(&self, v: usize) -> Option<W> {
+        self.dist[v]
+    }
+
+    /// 最短経路で `v` の1つ前のノード。始点と到達できないノードは `None`。
+    ///
+    /// # Panics
+    /// `v` が存在しないノードの場合
+    pub fn predecessor(&self, v: usize) -> Option<usize> {
+        self.pred[v]
+    }
+
+    /// 始点から `target` への最短経路のノードを、始点と `target` を含めて順に返す。
+    /// 到達できなければ `None`。
+    ///
+    /// # Panics
+    /// `target` が存在しないノードの場合
+    pub fn path_to(&self, target: usize) -> Option<Vec<usize>> {
+        self.dist[target]?;
+
+        let mut path = vec![target];
+        let mut v = target;
+        while let Some(u) = self.pred[v] {
+            path.push(u);
+            v = u;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// 始点から到達できる負閉路（重みの和が負の閉路）
+///
+/// 負閉路を通れば距離をいくらでも小さくできるため、最短経路が存在しない。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NegativeCycle {
+    /// 閉路のノードを辺の向きに並べたもの。最後のノードから最初のノードへの辺で閉じる。
+    pub nodes: Vec<usize>,
+}
+
+impl fmt::Display for NegativeCycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "negative cycle:")?;
+        for u in self.nodes.iter().chain(self.nodes.first()) {
+            write!(f, " {u}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for NegativeCycle {}
+
+/// ダイクストラ法 (O((n + m) log n))
+/// 負の重みがないグラフで、`start` から各ノードへの最短経路を求める。
+/// 負の重みの辺があるグラフには [`bellman_ford`] か [`spfa`] を使う。
+///
+/// 1. `start` の距離を 0、それ以外を無限大とし、`start` を優先度付きキューに入れる。
+/// 2. キューから距離が最小のノードを取り出し、そこから出る辺で隣のノードの距離を更新できれば、
+///    距離と親を更新してキューに入れる。
+/// 3. キューが空になるまで繰り返す。
+///
+/// 距離が [`Weight::checked_add`] で表せなくなる辺は使わない。
+/// そのような経路でしか到達できないノードは、到達できないものとする。
+///
+/// # Panics
+/// `start` が存在しないノードの場合と、負の重みの辺がある場合（[`Graph::negative_edge`] で確かめられる）
+pub fn dijkstra<W: Weight>(graph: &Graph<W>, start: usize) -> ShortestPaths<W> {
+    check_node(graph, start, "start");
+    dijkstra_impl(graph, start, None)
+}
+
+/// `target` だけへの最短経路を求める [`dijkstra`]。
+/// `target` をキューから取り出した時点で探索を打ち切り、`(最短距離, 経路)` を返す。到達できなければ `None`。
+///
+/// # Panics
+/// `start` か `target` が存在しないノードの場合と、負の重みの辺がある場合
+pub fn dijkstra_to<W: Weight>(
+    graph: &Graph<W>,
+    start: usize,
+    target: usize,
+) -> Option<(W, Vec<usize>)> {
+    check_node(graph, start, "start");
+    check_node(graph, target, "target");
+    let paths = dijkstra_impl(graph, start, Some(target));
+    Some((paths.distance(target)?, paths.path_to(target)?))
+}
+
+fn check_node<W: Weight>(graph: &Graph<W>, u: usize, name: &str) {
+    if let Err(error) = graph.check_node(u) {
+        panic!("invalid {name}: {error}");
+    }
+}
+
+/// `target` があれば、`target` の距離が確定した時点で打ち切る。
+fn dijkstra_impl<W: Weight>(
+    graph: &Graph<W>,
+    start: usize,
+    target: Option<usize>,
+) -> ShortestPaths<W> {
+    search(graph, start, target, |_| W::zero()).0
+}
+
+/// 1つの目的地への探索の結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Search<W = u32> {
+    /// `(最短距離, 経路)`。到達できなければ `None`。
+    pub path: Option<(W, Vec<usize>)>,
+    /// キューから取り出して隣を調べたノードの数（展開数）。同じノードを何度も展開した場合は重複して数える。
+    pub expanded: usize,
+}
+
+/// A* 探索 (最悪: O((n + m) log n))
+/// `heuristic(u)` を u から `target` への距離の推定値として、推定値の小さいノードから先に調べる [`dijkstra_to`]。
+///
+/// 1. `start` をキューに入れる。キューの優先度は、始点からの距離に推定値を足した値とする。
+/// 2. キューから優先度が最小のノードを取り出し、隣のノードの距離を更新できれば、距離と親を更新してキューに入れる。
+/// 3. `target` を取り出したら終了する。
+///
+/// 推定値が実際の距離を超えない（許容的な）ヒューリスティックなら、最短経路が求まる。
+/// 推定値が正確なほど、展開するノードが少なくなる。`|_| W::zero()` を渡すとダイクストラ法と同じになる。
+///
+/// # Panics
+/// `start` か `target` が存在しないノードの場合と、負の重みの辺がある場合
+pub fn astar<W, H>(graph: &Graph<W>, start: usize, target: usize, heuristic: H) -> Search<W>
+where
+    W: Weight,
+    H: FnMut(usize) -> W,
+{
+    check_node(graph, start, "start");
+    check_node(graph, target, "target");
+    let (paths, expanded) = search(graph, start, Some(target), heuristic);
+    let path = paths
+        .distance(target)
+        .and_then(|d| Some((d, paths.path_to(target)?)));
+    Search { path, expanded }
+}
+
+/// [`astar`] の本体。`heuristic` が常に 0 ならダイクストラ法になる。最短経路と展開数を返す。
+fn search<W, H>(
+    graph: &Graph<W>,
+    start: usize,
+    target: Option<usize>,
+    mut heuristic: H,
+) -> (ShortestPaths<W>, usize)
+where
+    W: Weight,
+    H: FnMut(usize) -> W,
+{
+    if let Some((u, v, _)) = graph.negative_edge() {
+        panic!("negative weight on edge ({u}, {v}): use bellman_ford or spfa");
+    }
+
+    let n = graph.node_count();
+    let mut dist = vec![None; n];
+    let mut pred = vec![None; n];
+    let mut heap = BinaryHeap::new();
+    let mut expanded = 0;
+
+    dist[start] = Some(W::zero());
+    // (推定距離, 距離, ノード)。推定距離が等しければ、目的地に近い（距離の大きい）ノードを先に調べる
+    heap.push(Reverse((heuristic(start), Reverse(W::zero()), start)));
+
+    while let Some(Reverse((_, Reverse(d), u))) = heap.pop() {
+        if dist[u].is_some_and(|du| d > du) {
+            continue; // 古い距離のエントリはスキップ
+        }
+        expanded += 1;
+        if target == Some(u) {
+            break;
+        }
+
+        for (v, w) in graph.neighbors(u) {
+            let Some(next_dist) = d.checked_add(w) else {
+                continue;
+            };
+            if dist[v].is_some_and(|dv| next_dist >= dv) {
+                continue;
+            }
+            // 推定距離が表せないノードは、target への経路の距離も表せない
+            let Some(estimate) = next_dist.checked_add(heuristic(v)) else {
+                continue;
+            };
+
+            dist[v] = Some(next_dist);
+            pred[v] = Some(u);
+            heap.push(Reverse((estimate, Reverse(next_dist), v)));
+        }
+    }
+
+    (ShortestPaths { start, dist, pred }, expanded)
+}
+
+/// ベルマン-フォード法 (O(nm))
+/// 負の重みの辺があるグラフで、`start` から各ノードへの最短経路を求める。
+///
+/// 1. `start` の距離を 0、それ以外を無限大とする。
+/// 2. 全ての辺 (u, v) について、u を経由して v の距離を更新できれば、距離と親を更新する。
+/// 3. 更新がなくなれば終了する。最短経路の辺は n - 1 本以下なので、n - 1 回繰り返せば距離が確定する。
+/// 4. n 回目でも更新が起こるなら負閉路がある。最後に更新したノードから親をたどり、閉路を見つける。
+///
+/// 無向グラフの辺は両方向の2本の有向辺なので、重みが負の辺 {u, v} はそれだけで負閉路 u → v → u になる。
+///
+/// 重みが負の辺で距離が [`Weight::checked_add`] で表せないほど小さくなる場合は、その辺を親として親をたどり、
+/// 閉路になれば負閉路とする（負閉路を回り続けると距離はいくらでも小さくなる）。閉路にならなければ、その経路は使わない。
+///
+/// # Errors
+/// `start` から到達できる負閉路があれば、その閉路を返す。
+///
+/// # Panics
+/// `start` が存在しないノードの場合
+pub fn bellman_ford<W: Weight>(
+    graph: &Graph<W>,
+    start: usize,
+) -> Result<ShortestPaths<W>, NegativeCycle> {
+    check_node(graph, start, "start");
+
+    let n = graph.node_count();
+    let mut dist = vec![None; n];
+    let mut pred = vec![None; n];
+    dist[start] = Some(W::zero());
+
+    for round in 1..=n {
+        let mut updated = None;
+        for (u, v, w) in graph.edges() {
+            let Some(du) = dist[u] else {
+                continue;
+            };
+            let Some(next_dist) = du.checked_add(w) else {
+                if let Some(nodes) = underflow_cycle(&mut pred, u, v, w) {
+                    return Err(NegativeCycle { nodes });
+                }
+                continue;
+            };
+            if dist[v].is_none_or(|dv| next_dist < dv) {
+                dist[v] = Some(next_dist);
+                pred[v] = Some(u);
+                updated = Some(v);
+            }
+        }
+
+        match updated {
+            None => break,
+            Some(v) if round == n => {
+                // 親の辺がなす閉路は負閉路で、n 回目に更新されたノードの親をたどると見つかる
+                let nodes = std::iter::once(v)
+                    .chain(0..n)
+                    .find_map(|u| predecessor_cycle(&pred, u))
+                    .expect("predecessor graph has a cycle");
+                return Err(NegativeCycle { nodes });
+            }
+            Some(_) => {}
+        }
+    }
+
+    Ok(ShortestPaths { start, dist, pred })
+}
+
+/// SPFA（Shortest Path Faster Algorithm） (平均: O(m) 程度, 最悪: O(nm))
+/// [`bellman_ford`] で、距離が更新されたノードから出る辺だけを調べる。
+///
+/// 1. `start` の距離を 0 とし、キューに入れる。
+/// 2. キューからノード u を取り出し、u から出る辺で隣のノード v の距離を更新できれば、
+///    距離と親を更新して、v がキューになければ入れる。
+/// 3. キューが空になるまで繰り返す。
+/// 4. 経路の辺の本数が n 以上になったら負閉路を疑い、親をたどって閉路があれば負閉路として返す。
+///
+/// [`bellman_ford`] と同じく、無向グラフの重みが負の辺は長さ2の負閉路になり、
+/// 表せないほど小さくなる距離は負閉路の手がかりとして扱う。
+///
+/// # Errors
+/// `start` から到達できる負閉路があれば、その閉路を返す。
+///
+/// # Panics
+/// `start` が存在しないノードの場合
+pub fn spfa<W: Weight>(graph: &Graph<W>, start: usize) -> Result<ShortestPaths<W>, NegativeCycle> {
+    check_node(graph, start, "start");
+
+    let n = graph.node_count();
+    let mut dist = vec![None; n];
+    let mut pred = vec![None; n];
+    // 親をたどった経路の辺の本数
+    let mut hops = vec![0; n];
+    let mut queued = vec![false; n];
+    let mut queue = VecDeque::from([start]);
+    dist[start] = Some(W::zero());
+    queued[start] = true;
+
+    while let Some(u) = queue.pop_front() {
+        queued[u] = false;
+        let Some(du) = dist[u] else {
+            continue;
+        };
+
+        for (v, w) in graph.neighbors(u) {
+            let Some(next_dist) = du.checked_add(w) else {
+                if let Some(nodes) = underflow_cycle(&mut pred, u, v, w) {
+                    return Err(NegativeCycle { nodes });
+                }
+                continue;
+            };
+            if dist[v].is_some_and(|dv| next_dist >= dv) {
+                continue;
+            }
+
+            dist[v] = Some(next_dist);
+            pred[v] = Some(u);
+            hops[v] = hops[u] + 1;
+            if hops[v] >= n
+                && let Some(nodes) = predecessor_cycle(&pred, v)
+            {
+                return Err(NegativeCycle { nodes });
+            }
+            if !queued[v] {
+                queue.push_back(v);
+                queued[v] = true;
+            }
+        }
+    }
+
+    Ok(ShortestPaths { start, dist, pred })
+}
+
+/// 重みが負の辺 (u, v) で v の距離が表せないほど小さくなるとき、v の親を u にすると閉路ができるなら、その閉路を返す。
+/// 閉路ができなければ親を元に戻す。
+fn underflow_cycle<W: Weight>(
+    pred: &mut [Option<usize>],
+    u: usize,
+    v: usize,
+    w: W,
+) -> Option<Vec<usize>> {
+    if w >= W::zero() {
+        return None;
+    }
+    let old = pred[v].replace(u);
+    let cycle = predecessor_cycle(pred, v);
+    if cycle.is_none() {
+        pred[v] = old;
+    }
+    cycle
+}
+
+/// `from` から親をたどって閉路に入れば、その閉路を辺の向きに並べて返す。
+fn predecessor_cycle(pred: &[Option<usize>], from: usize) -> Option<Vec<usize>> {
+    // walk[i] は from から親を i 回たどったノード。position はその逆引き
+    let mut position = vec![None; pred.len()];
+    let mut walk = Vec::new();
+    let mut u = from;
+    loop {
+        if let Some(i) = position[u] {
+            let mut cycle = walk.split_off(i);
+            cycle.reverse();
+            return Some(cycle);
+        }
+        position[u] = Some(walk.len());
+        walk.push(u);
+        u = pred[u]?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::check::{self, check, shrink_vec};
+    use crate::graph::weight::OrderedF64;
+    use crate::graph::{Direction, GraphBuilder};
+
+    #[test]
+    fn test_dijkstra() {
+        let graph = GraphBuilder::new(Direction::Undirected, 4)
+            .weighted_edge(0, 1, 1)
+            .weighted_edge(0, 2, 4)
+            .weighted_edge(1, 2, 2)
+            .weighted_edge(1, 3, 5)
+            .weighted_edge(2, 3, 1)
+            .build()
+            .unwrap();
+        let paths = dijkstra(&graph, 0);
+        assert_eq!(paths.distances(), [Some(0), Some(1), Some(3), Some(4)]);
+        assert_eq!(paths.path_to(3), Some(vec![0, 1, 2, 3]));
+        assert_eq!(paths.path_to(0), Some(vec![0]));
+        assert_eq!(paths.predecessor(2), Some(1));
+        assert_eq!(paths.predecessor(0), None);
+
+        assert_eq!(dijkstra_to(&graph, 3, 0), Some((4, vec![3, 2, 1, 0])));
+    }
+
+    #[test]
+    fn test_dijkstra_unweighted() {
+        // 重みなしのグラフでは辺の本数
+        let graph = GraphBuilder::new(Direction::Directed, 5)
+            .edge(0, 1)
+            .edge(1, 2)
+            .edge(0, 3)
+            .edge(3, 2)
+            .build()
+            .unwrap();
+        let paths = dijkstra(&graph, 0);
+        assert_eq!(
+            paths.distances(),
+            [Some(0), Some(1), Some(2), Some(1), None]
+        );
+        assert_eq!(paths.path_to(4), None);
+        assert_eq!(dijkstra_to(&graph, 0, 4), None);
+    }
+
+    #[test]
+    fn test_dijkstra_overflow() {
+        // 0 -> 1 -> 2 の距離は u32 に収まらないため、2 へは 0 -> 3 -> 2 で到達する
+        let graph = GraphBuilder::new(Direction::Directed, 5)
+            .weighted_edge(0, 1, u32::MAX)
+            .weighted_edge(1, 2, 1)
+            .weighted_edge(0, 3, u32::MAX - 2)
+            .weighted_edge(3, 2, 2)
+            .weighted_edge(1, 4, 1)
+            .build()
+            .unwrap();
+        let paths = dijkstra(&graph, 0);
+        assert_eq!(paths.distance(1), Some(u32::MAX));
+        assert_eq!(paths.path_to(2), Some(vec![0, 3, 2]));
+        assert_eq!(paths.distance(4), None);
+    }
+
+    #[test]
+    fn test_dijkstra_to_stops_early() {
+        // 0 - 1 - 2 - ... - 9 の一直線のグラフで、1 の距離が確定すると 3 以降は調べない
+        let mut builder = GraphBuilder::new(Direction::Undirected, 10);
+        for u in 0..9 {
+            builder.edge(u, u + 1);
+        }
+        let graph = builder.build().unwrap();
+        assert_eq!(dijkstra_to(&graph, 0, 1), Some((1, vec![0, 1])));
+        assert_eq!(dijkstra_impl(&graph, 0, Some(1)).distance(3), None);
+    }
+
+    #[test]
+    fn test_dijkstra_fractional_weights() {
+        let graph = GraphBuilder::with_weights(Direction::Directed, 3)
+            .weighted_edge(0, 1, OrderedF64(0.5))
+            .weighted_edge(1, 2, OrderedF64(0.25))
+            .weighted_edge(0, 2, OrderedF64(1.0))
+            .build()
+            .unwrap();
+        assert_eq!(
+            dijkstra_to(&graph, 0, 2),
+            Some((OrderedF64(0.75), vec![0, 1, 2]))
+        );
+    }
+
+    #[test]
+    fn test_dijkstra_negative_zero_weight() {
+        // -0.0 の重みは負の辺ではない
+        let graph = GraphBuilder::with_weights(Direction::Undirected, 3)
+            .weighted_edge(0, 1, OrderedF64(-0.0))
+            .weighted_edge(1, 2, OrderedF64(0.5))
+            .build()
+            .unwrap();
+        assert_eq!(graph.negative_edge(), None);
+        assert_eq!(
+            dijkstra(&graph, 0).distances(),
+            [0.0, 0.0, 0.5].map(|d| Some(OrderedF64(d)))
+        );
+        assert!(bellman_ford(&graph, 0).is_ok());
+    }
+
+    #[test]
+    fn test_dijkstra_multi_criteria() {
+        // 重みを (コスト, 辺の本数) にすると、コストが等しい経路のうち辺の少ない方を選ぶ
+        let mut builder = GraphBuilder::with_weights(Direction::Directed, 4);
+        for (u, v, cost) in [(0, 1, 1), (1, 2, 1), (2, 3, 1), (0, 3, 3)] {
+            builder.weighted_edge(u, v, (cost, 1));
+        }
+        let graph = builder.build().unwrap();
+        assert_eq!(dijkstra_to(&graph, 0, 3), Some(((3u64, 1u32), vec![0, 3])));
+    }
+
+    #[test]
+    fn test_dijkstra_large_weights() {
+        // u32 に収まらない距離も、u128 なら表せる
+        let graph = GraphBuilder::with_weights(Direction::Directed, 3)
+            .weighted_edge(0, 1, u128::from(u64::MAX))
+            .weighted_edge(1, 2, u128::from(u64::MAX))
+            .build()
+            .unwrap();
+        let expected = 2 * u128::from(u64::MAX);
+        assert_eq!(dijkstra(&graph, 0).distance(2), Some(expected));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid start")]
+    fn test_dijkstra_invalid_start() {
+        let graph = GraphBuilder::new(Direction::Directed, 2).build().unwrap();
+        dijkstra(&graph, 2);
+    }
+
+    /// 辺のリストから、全ての辺での距離の更新を n - 1 回繰り返して最短距離を求める。到達できないノードは `None`。
+    fn brute_force(n: usize, edges: &[(usize, usize, u32)], start: usize) -> Vec<Option<u32>> {
+        let mut dist = vec![u64::MAX; n];
+        dist[start] = 0;
+        for _ in 1..n {
+            for &(u, v, w) in edges {
+                if dist[u] != u64::MAX {
+                    dist[v] = dist[v].min(dist[u] + u64::from(w));
+                }
+            }
+        }
+        dist.into_iter().map(|d| u32::try_from(d).ok()).collect()
+    }
+
+    #[test]
+    fn test_differential_dijkstra() {
+        // (ノード数, 有向辺 (from, to, weight) のリスト)
+        let generate = |rng: &mut rand::rngs::StdRng| {
+            let n = rng.random_range(1..=7);
+            let m = rng.random_range(0..=n * n);
+            let edges = (0..m)
+                .map(|_| {
+                    let u = rng.random_range(0..n);
+                    let v = rng.random_range(0..n);
+                    (u, v, rng.random_range(0..50))
+                })
+                .collect::<Vec<_>>();
+            (n, edges)
+        };
+        let shrink = |(n, edges): &(usize, Vec<(usize, usize, u32)>)| {
+            shrink_vec(edges, |&(u, v, w)| {
+                if w > 0 {
+                    vec![(u, v, 0), (u, v, w / 2)]
+                } else {
+                    vec![]
+                }
+            })
+            .into_iter()
+            .map(|edges| (*n, edges))
+            .collect()
+        };
+
+        check("dijkstra", generate, shrink, |(n, edges)| {
+            let mut builder = GraphBuilder::new(Direction::Directed, *n);
+            for &(u, v, w) in edges {
+                builder.weighted_edge(u, v, w);
+            }
+            let graph = builder.build().map_err(|e| e.to_string())?;
+            let expected = brute_force(*n, edges, 0);
+            let paths = dijkstra(&graph, 0);
+            check::equal(&expected[..], paths.distances())?;
+
+            // 経路は辺をたどれて、重みの和が最短距離になる
+            for (target, &distance) in expected.iter().enumerate() {
+                let Some(path) = paths.path_to(target) else {
+                    continue;
+                };
+                let cost = path.windows(2).try_fold(0, |cost, w| {
+                    let weight = graph
+                        .neighbors(w[0])
+                        .filter(|&(v, _)| v == w[1])
+                        .map(|(_, w)| w)
+                        .min();
+                    weight
+                        .map(|weight| cost + weight)
+                        .ok_or(format!("no edge in {path:?}"))
+                })?;
+                check::equal((0, distance), (path[0], Some(cost)))?;
+
+                let single = dijkstra_to(&graph, 0, target).map(|(d, _)| d);
+                check::equal(distance, single)?;
+            }
+            Ok(())
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "negative weight on edge (1, 2)")]
+    fn test_dijkstra_rejects_negative_weights() {
+        let graph = GraphBuilder::with_weights(Direction::Directed, 3)
+            .weighted_edge(0, 1, 1)
+            .weighted_edge(1, 2, -1)
+            .build()
+            .unwrap();
+        assert_eq!(graph.negative_edge(), Some((1, 2, -1)));
+        dijkstra(&graph, 0);
+    }
+
+    #[test]
+    fn test_negative_weights() {
+        // 割引（負の重み）のある 0 -> 1 -> 3 -> 2 が最短
+        let graph = GraphBuilder::with_weights(Direction::Directed, 5)
+            .weighted_edge(0, 1, 4)
+            .weighted_edge(0, 2, 5)
+            .weighted_edge(1, 3, 2)
+            .weighted_edge(3, 2, -3)
+            .weighted_edge(2, 1, 1)
+            .build()
+            .unwrap();
+        for paths in [bellman_ford(&graph, 0), spfa(&graph, 0)] {
+            let paths = paths.unwrap();
+            assert_eq!(
+                paths.distances(),
+                [Some(0), Some(4), Some(3), Some(6), None]
+            );
+            assert_eq!(paths.path_to(2), Some(vec![0, 1, 3, 2]));
+        }
+    }
+
+    #[test]
+    fn test_negative_cycle() {
+        // 1 -> 2 -> 3 -> 1 の重みの和は -1
+        let graph = GraphBuilder::with_weights(Direction::Directed, 5)
+            .weighted_edge(0, 1, 1)
+            .weighted_edge(1, 2, 2)
+            .weighted_edge(2, 3, -4)
+            .weighted_edge(3, 1, 1)
+            .weighted_edge(3, 4, 1)
+            .build()
+            .unwrap();
+        for result in [bellman_ford(&graph, 0), spfa(&graph, 0)] {
+            let mut cycle = result.unwrap_err();
+            let first = cycle.nodes.iter().position(|&u| u == 1).unwrap();
+            cycle.nodes.rotate_left(first);
+            assert_eq!(cycle.nodes, [1, 2, 3]);
+            assert_eq!(cycle.to_string(), "negative cycle: 1 2 3 1");
+        }
+
+        // 始点から到達できない負閉路は関係ない
+        for paths in [bellman_ford(&graph, 4), spfa(&graph, 4)] {
+            assert_eq!(
+                paths.unwrap().distances(),
+                [None, None, None, None, Some(0)]
+            );
+        }
+    }
+
+    #[test]
+    fn test_undirected_negative_edge_is_cycle() {
+        // 無向グラフの負の辺 {1, 2} は 1 -> 2 -> 1 の負閉路
+        let graph = GraphBuilder::with_weights(Direction::Undirected, 3)
+            .weighted_edge(0, 1, 1)
+            .weighted_edge(1, 2, -1)
+            .build()
+            .unwrap();
+        assert_eq!(graph.negative_edge(), Some((1, 2, -1)));
+        for result in [bellman_ford(&graph, 0), spfa(&graph, 0)] {
+            let mut cycle = result.unwrap_err();
+            cycle.nodes.sort();
+            assert_eq!(cycle.nodes, [1, 2]);
+        }
+    }
+
+    #[test]
+    fn test_negative_cycle_underflow() {
+        // 閉路を1周するだけで距離が i32 の範囲を下回る
+        let graph = GraphBuilder::with_weights(Direction::Directed, 2)
+            .weighted_edge(0, 1, -1_500_000_000)
+            .weighted_edge(1, 0, -1_500_000_000)
+            .build()
+            .unwrap();
+        for result in [bellman_ford(&graph, 0), spfa(&graph, 0)] {
+            let mut cycle = result.unwrap_err();
+            cycle.nodes.sort();
+            assert_eq!(cycle.nodes, [0, 1]);
+        }
+
+        // 閉路のない経路で範囲を下回る場合は、その経路を使わない
+        let graph = GraphBuilder::with_weights(Direction::Directed, 3)
+            .weighted_edge(0, 1, -1_500_000_000)
+            .weighted_edge(1, 2, -1_500_000_000)
+            .build()
+            .unwrap();
+        for result in [bellman_ford(&graph, 0), spfa(&graph, 0)] {
+            assert_eq!(
+                result.unwrap().distances(),
+                [Some(0), Some(-1_500_000_000), None]
+            );
+        }
+    }
+
+    #[test]
+    fn test_arbitrage() {
+        // 為替レート r の辺の重みを -ln r とすると、積が 1 を超える両替の連鎖が負閉路になる
+        let rates = [
+            ("USD", "JPY", 150.0),
+            ("JPY", "EUR", 0.0062),
+            ("EUR", "USD", 1.09),
+            ("USD", "EUR", 0.91),
+        ];
+        let mut builder = GraphBuilder::with_weights(Direction::Directed, 0);
+        for name in ["USD", "JPY", "EUR"] {
+            builder.add_node(name);
+        }
+        let index = |name| {
+            ["USD", "JPY", "EUR"]
+                .iter()
+                .position(|&n| n == name)
+                .unwrap()
+        };
+        for (from, to, rate) in rates {
+            builder.weighted_edge(index(from), index(to), OrderedF64(-f64::ln(rate)));
+        }
+        let graph = builder.build().unwrap();
+
+        // 150 * 0.0062 * 1.09 = 1.0137 > 1
+        for result in [bellman_ford(&graph, 0), spfa(&graph, 0)] {
+            let cycle = result.unwrap_err();
+            let mut labels = cycle
+                .nodes
+                .iter()
+                .map(|&u| graph.label(u).unwrap())
+                .collect::<Vec<_>>();
+            labels.sort();
+            assert_eq!(labels, ["EUR", "JPY", "USD"]);
+        }
+    }
+
+    /// 辺 (u, v) の重みの最小値
+    fn min_weight(graph: &Graph<i32>, u: usize, v: usize) -> Option<i32> {
+        graph
+            .neighbors(u)
+            .filter(|&(to, _)| to == v)
+            .map(|(_, w)| w)
+            .min()
+    }
+
+    /// 最短経路の条件を満たすか確かめる。
+    ///
+    /// 1. どの辺でも距離を更新できない。
+    /// 2. 経路は辺をたどれて、重みの和が距離になる。
+    fn verify_paths(graph: &Graph<i32>, paths: &ShortestPaths<i32>) -> Result<(), String> {
+        for (u, v, w) in graph.edges() {
+            if let Some(du) = paths.distance(u)
+                && paths.distance(v).is_none_or(|dv| du + w < dv)
+            {
+                return Err(format!("edge ({u}, {v}) can be relaxed"));
+            }
+        }
+        for target in 0..graph.node_count() {
+            let Some(path) = paths.path_to(target) else {
+                continue;
+            };
+            let mut cost = 0;
+            for w in path.windows(2) {
+                cost += min_weight(graph, w[0], w[1]).ok_or(format!("no edge in {path:?}"))?;
+            }
+            check::equal(paths.distance(target), Some(cost))?;
+        }
+        Ok(())
+    }
+
+    /// 閉路の辺をたどれて、重みの和が負か確かめる。
+    fn verify_cycle(graph: &Graph<i32>, cycle: &NegativeCycle) -> Result<(), String> {
+        let nodes = &cycle.nodes;
+        let mut cost = 0;
+        for (i, &u) in nodes.iter().enumerate() {
+            let v = nodes[(i + 1) % nodes.len()];
+            cost += min_weight(graph, u, v).ok_or(format!("no edge in {cycle}"))?;
+        }
+        if cost < 0 {
+            Ok(())
+        } else {
+            Err(format!("{cycle} has cost {cost}"))
+        }
+    }
+
+    #[test]
+    fn test_differential_negative_weights() {
+        let generate = |rng: &mut rand::rngs::StdRng| {
+            let n = rng.random_range(1..=6);
+            let m = rng.random_range(0..=2 * n);
+            let edges = (0..m)
+                .map(|_| {
+                    let u = rng.random_range(0..n);
+                    let v = rng.random_range(0..n);
+                    (u, v, rng.random_range(-10..30))
+                })
+                .collect::<Vec<_>>();
+            (n, edges)
+        };
+        let shrink = |(n, edges): &(usize, Vec<(usize, usize, i32)>)| {
+            shrink_vec(edges, |&(u, v, w)| {
+                if w != 0 {
+                    vec![(u, v, 0), (u, v, w / 2)]
+                } else {
+                    vec![]
+                }
+            })
+            .into_iter()
+            .map(|edges| (*n, edges))
+            .collect()
+        };
+
+        check("bellman_ford and spfa", generate, shrink, |(n, edges)| {
+            let mut builder = GraphBuilder::with_weights(Direction::Directed, *n);
+            for &(u, v, w) in edges {
+                builder.weighted_edge(u, v, w);
+            }
+            let graph = builder.build().map_err(|e| e.to_string())?;
+
+            match (bellman_ford(&graph, 0), spfa(&graph, 0)) {
+                (Ok(expected), Ok(paths)) => {
+                    verify_paths(&graph, &expected)?;
+                    check::equal(expected.distances(), paths.distances())?;
+                    verify_paths(&graph, &paths)?;
+                    if graph.negative_edge().is_none() {
+                        check::equal(expected.distances(), dijkstra(&graph, 0).distances())?;
+                    }
+                    Ok(())
+                }
+                (Err(expected), Err(cycle)) => {
+                    verify_cycle(&graph, &expected)?;
+                    verify_cycle(&graph, &cycle)
+                }
+                (expected, actual) => Err(format!("bellman_ford: {expected:?}, spfa: {actual:?}")),
+            }
+        });
+    }
+}
@@ -0,0 +1,160 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+/// 辺の重みと経路の距離の型
+///
+/// 距離は重みの和で、全順序で比べる。組 `(A, B)` は辞書式順序で比べ、
+/// 例えば `(コスト, 辺の本数)` ならコストが等しい経路のうち辺の少ないものを選べる。
+pub trait Weight: Copy + Ord {
+    /// 長さ 0 の経路の距離
+    fn zero() -> Self;
+
+    /// 重みなしのグラフの辺の重み
+    fn one() -> Self;
+
+    /// 和。表せない場合は `None`。
+    fn checked_add(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_weight {
+    ($($t:ty),*) => {
+        $(
+            impl Weight for $t {
+                fn zero() -> Self {
+                    0
+                }
+
+                fn one() -> Self {
+                    1
+                }
+
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_weight!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize
+);
+
+impl<A: Weight, B: Weight> Weight for (A, B) {
+    fn zero() -> Self {
+        (A::zero(), B::zero())
+    }
+
+    fn one() -> Self {
+        (A::one(), B::one())
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        Some((self.0.checked_add(other.0)?, self.1.checked_add(other.1)?))
+    }
+}
+
+/// [`f64::total_cmp`] で全順序を付けた `f64` の重み
+///
+/// `-0.0` は `0.0` と等しいものとして比べる（負の重みとはみなさない）。
+/// 和が NaN になる場合（NaN の重みや、符号の異なる無限大の和）は表せないものとする。
+/// 大きすぎる和は無限大になる。
+#[derive(Debug, Clone, Copy)]
+pub struct OrderedF64(pub f64);
+
+impl PartialEq for OrderedF64 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OrderedF64 {}
+
+impl PartialOrd for OrderedF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedF64 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // 0.0 を足して -0.0 を 0.0 にそろえる
+        (0.0 + self.0).total_cmp(&(0.0 + other.0))
+    }
+}
+
+impl fmt::Display for OrderedF64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<f64> for OrderedF64 {
+    fn from(x: f64) -> Self {
+        Self(x)
+    }
+}
+
+impl Weight for OrderedF64 {
+    fn zero() -> Self {
+        Self(0.0)
+    }
+
+    fn one() -> Self {
+        Self(1.0)
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        let sum = self.0 + other.0;
+        (!sum.is_nan()).then_some(Self(sum))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integer_weights() {
+        assert_eq!(Weight::checked_add(u32::MAX - 1, 1), Some(u32::MAX));
+        assert_eq!(Weight::checked_add(u32::MAX, 1), None);
+        assert_eq!(Weight::checked_add(u64::from(u32::MAX), 1), Some(1 << 32));
+        assert_eq!(<u128 as Weight>::one(), 1);
+    }
+
+    #[test]
+    fn test_pair_weights() {
+        // コストが等しければ、辺の少ない方が小さい
+        let a: (u32, u8) = (10, 3);
+        let b = (10, 2);
+        assert!(b < a && a < (11, 0));
+        assert_eq!(a.checked_add(<(u32, u8)>::one()), Some((11, 4)));
+        assert_eq!((0, u8::MAX).checked_add((1, 1)), None::<(u32, u8)>);
+    }
+
+    #[test]
+    fn test_ordered_f64() {
+        let mut v = [2.5, -1.0, 0.5].map(OrderedF64);
+        v.sort();
+        assert_eq!(v, [-1.0, 0.5, 2.5].map(OrderedF64));
+
+        assert_eq!(
+            OrderedF64(0.25).checked_add(OrderedF64(0.5)),
+            Some(OrderedF64(0.75))
+        );
+        assert_eq!(OrderedF64(1.0).checked_add(OrderedF64(f64::NAN)), None);
+        assert_eq!(
+            OrderedF64(f64::INFINITY).checked_add(OrderedF64(f64::NEG_INFINITY)),
+            None
+        );
+        assert_eq!(
+            OrderedF64(f64::MAX).checked_add(OrderedF64(f64::MAX)),
+            Some(OrderedF64(f64::INFINITY))
+        );
+
+        // -0.0 は 0.0 と等しく、負ではない
+        assert_eq!(OrderedF64(-0.0), OrderedF64(0.0));
+        assert!(OrderedF64(-0.0) >= OrderedF64::zero());
+        assert!(OrderedF64(-f64::MIN_POSITIVE) < OrderedF64(-0.0));
+    }
+}
@@ -54,7 +54,7 @@ fn dfs(
 
     // 一番最後の係数の場合、target が coeff の倍数である必要がある。
     if index == coeffs.len() - 1 {
-        if target % coeff == 0 {
+        if target.is_multiple_of(coeff) {
             let x = target / coeff;
             if x >= min_value {
                 current.push(x);
@@ -110,7 +110,10 @@ pub fn count_total_prime_factors(n: usize) -> Vec<usize> {
 
 #[cfg(test)]
 mod tests {
+    use rand::Rng;
+
     use super::*;
+    use crate::check::{self, check, shrink_usize, shrink_vec};
 
     #[test]
     fn test_find_solutions_two() {
@@ -172,4 +175,57 @@ mod tests {
         let solutions = solve_asc(&coeffs, 278_790);
         assert_eq!(solutions.len(), 159_432);
     }
+
+    /// 全ての変数に 1..=target を当てはめ、辞書順に解を列挙する。
+    fn brute_force(coeffs: &[usize], target: usize) -> Vec<Vec<usize>> {
+        let mut solutions = vec![vec![]];
+        for _ in coeffs {
+            solutions = solutions
+                .into_iter()
+                .flat_map(|s| {
+                    (1..=target).map(move |x| {
+                        let mut s = s.clone();
+                        s.push(x);
+                        s
+                    })
+                })
+                .collect();
+        }
+        solutions.retain(|s| coeffs.iter().zip(s).map(|(c, x)| c * x).sum::<usize>() == target);
+        solutions
+    }
+
+    #[test]
+    fn test_differential_solve() {
+        let generate = |rng: &mut rand::rngs::StdRng| {
+            let len = rng.random_range(1..=3);
+            let coeffs = (0..len)
+                .map(|_| rng.random_range(1..=6))
+                .collect::<Vec<usize>>();
+            (coeffs, rng.random_range(0..=20))
+        };
+        // 係数は 1 以上で、少なくとも1つ必要
+        let shrink = |(coeffs, target): &(Vec<usize>, usize)| {
+            let mut candidates = shrink_usize(target)
+                .into_iter()
+                .map(|t| (coeffs.clone(), t))
+                .collect::<Vec<_>>();
+            candidates.extend(
+                shrink_vec(coeffs, shrink_usize)
+                    .into_iter()
+                    .filter(|c| !c.is_empty() && !c.contains(&0))
+                    .map(|c| (c, *target)),
+            );
+            candidates
+        };
+
+        check("solve", generate, shrink, |(coeffs, target)| {
+            check::equal(brute_force(coeffs, *target), solve(coeffs, *target))
+        });
+        check("solve_asc", generate, shrink, |(coeffs, target)| {
+            let mut expected = brute_force(coeffs, *target);
+            expected.retain(|s| s.windows(2).all(|w| w[0] <= w[1]));
+            check::equal(expected, solve_asc(coeffs, *target))
+        });
+    }
 }
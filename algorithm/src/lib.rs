@@ -1,4 +1,10 @@
+pub mod bench;
+#[cfg(test)]
+mod check;
+pub mod cli;
 pub mod correlation;
 pub mod graph;
 pub mod indefinite_equation;
+pub mod metrics;
 pub mod sort;
+pub mod sorted;
@@ -0,0 +1,222 @@
+//! 入力がどの程度ソート済みかを表す指標（presortedness）。
+
+use std::mem;
+
+/// 入力の乱れ具合をまとめたもの。いずれもソート済みの入力で最小になる。
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Presortedness {
+    /// 転倒数 ([`inversions`])
+    pub inversions: usize,
+    /// 昇順の区間の数 ([`runs`])
+    pub runs: usize,
+    /// ソート済みにするために取り除く要素の最小数 ([`rem`])
+    pub rem: usize,
+    /// 転倒している組の最大の距離 ([`displacement`])
+    pub displacement: usize,
+}
+
+/// 全ての指標を求める。
+pub fn measure<T: PartialOrd + Clone>(arr: &[T]) -> Presortedness {
+    Presortedness {
+        inversions: inversions(arr),
+        runs: runs(arr),
+        rem: rem(arr),
+        displacement: displacement(arr),
+    }
+}
+
+/// 転倒数 (O(n log n))
+/// `i < j` かつ `arr[i] > arr[j]` となる組の数。ソート済みなら 0、逆順なら n(n-1)/2 になる。
+///
+/// マージソートのマージで右側の要素を先に取り出すとき、左側に残っている要素は全てその要素より大きい。
+/// その個数を足し合わせると転倒数になる。
+pub fn inversions<T: PartialOrd + Clone>(arr: &[T]) -> usize {
+    let mut v = arr.to_vec();
+    let mut buf = arr.to_vec();
+    count_inversions(&mut v, &mut buf)
+}
+
+/// `v` をソートし、その間に数えた転倒数を返す。`buf` は `v` と同じ長さの作業領域。
+fn count_inversions<T: PartialOrd>(v: &mut [T], buf: &mut [T]) -> usize {
+    let n = v.len();
+    if n <= 1 {
+        return 0;
+    }
+
+    let mid = n / 2;
+    let mut count = {
+        let (left, right) = v.split_at_mut(mid);
+        let (buf_left, buf_right) = buf.split_at_mut(mid);
+        count_inversions(left, buf_left) + count_inversions(right, buf_right)
+    };
+
+    let (mut i, mut j) = (0, mid);
+    for elem in buf.iter_mut() {
+        let from = if j < n && (i >= mid || v[j] < v[i]) {
+            // v[i..mid] は全て v[j] より大きい
+            count += mid - i;
+            j += 1;
+            j - 1
+        } else {
+            i += 1;
+            i - 1
+        };
+        mem::swap(elem, &mut v[from]);
+    }
+    v.swap_with_slice(buf);
+
+    count
+}
+
+/// 昇順の区間（ラン）の数 (O(n))
+/// 隣り合う要素が降順になっている箇所で区切った区間の数。ソート済みなら 1、逆順なら n になる。空なら 0。
+pub fn runs<T: PartialOrd>(arr: &[T]) -> usize {
+    if arr.is_empty() {
+        return 0;
+    }
+    1 + arr.windows(2).filter(|w| w[1] < w[0]).count()
+}
+
+/// Rem (O(n log n))
+/// 残りがソート済みになるように取り除く要素の最小数。n から最長非減少部分列の長さを引いたもの。
+///
+/// 最長非減少部分列の長さは、ペイシェンスソートの山の数で求める。
+/// 1. 要素を先頭から順に見て、山の一番上の要素がその要素より大きい山のうち、最も左の山に積む。
+/// 2. そのような山がなければ、右端に新しい山を作る。
+///
+/// 山の一番上の要素は左から昇順に並ぶので、積む山は二分探索で探せる。
+pub fn rem<T: PartialOrd + Clone>(arr: &[T]) -> usize {
+    // 各山の一番上の要素
+    let mut tops: Vec<T> = Vec::new();
+
+    for x in arr {
+        let pile = tops.partition_point(|top| top <= x);
+        if pile == tops.len() {
+            tops.push(x.clone());
+        } else {
+            tops[pile] = x.clone();
+        }
+    }
+
+    arr.len() - tops.len()
+}
+
+/// Dis (O(n log n))
+/// `i < j` かつ `arr[i] > arr[j]` となる組のうち、`j - i` の最大値。転倒がなければ 0。
+///
+/// 各 `j` について、`arr[j]` より大きい要素のうち最も左のものを探す。
+/// 先頭からの最大値は非減少なので、それが `arr[j]` を超える最初の位置を二分探索で求める。
+pub fn displacement<T: PartialOrd + Clone>(arr: &[T]) -> usize {
+    // prefix_max[i] は arr[..=i] の最大値
+    let mut prefix_max: Vec<T> = Vec::with_capacity(arr.len());
+    let mut max = 0;
+
+    for (j, x) in arr.iter().enumerate() {
+        let i = prefix_max.partition_point(|m| m <= x);
+        if i < j {
+            max = max.max(j - i);
+        }
+
+        let next = match prefix_max.last() {
+            Some(m) if x <= m => m.clone(),
+            _ => x.clone(),
+        };
+        prefix_max.push(next);
+    }
+
+    max
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sort::make_random_vector;
+
+    /// 全ての組を調べる O(n^2) の実装
+    fn brute_force(arr: &[i32]) -> Presortedness {
+        let n = arr.len();
+        let mut inversions = 0;
+        let mut displacement = 0;
+        for i in 0..n {
+            for j in i + 1..n {
+                if arr[i] > arr[j] {
+                    inversions += 1;
+                    displacement = displacement.max(j - i);
+                }
+            }
+        }
+
+        // lis[i] は arr[i] で終わる最長非減少部分列の長さ
+        let mut lis = vec![1; n];
+        for j in 0..n {
+            for i in 0..j {
+                if arr[i] <= arr[j] {
+                    lis[j] = lis[j].max(lis[i] + 1);
+                }
+            }
+        }
+
+        Presortedness {
+            inversions,
+            runs: runs(arr),
+            rem: n - lis.into_iter().max().unwrap_or(0),
+            displacement,
+        }
+    }
+
+    #[test]
+    fn test_sorted_and_reversed() {
+        let sorted = (0..10).collect::<Vec<_>>();
+        assert_eq!(
+            measure(&sorted),
+            Presortedness {
+                inversions: 0,
+                runs: 1,
+                rem: 0,
+                displacement: 0
+            }
+        );
+
+        let reversed = (0..10).rev().collect::<Vec<_>>();
+        assert_eq!(
+            measure(&reversed),
+            Presortedness {
+                inversions: 45,
+                runs: 10,
+                rem: 9,
+                displacement: 9
+            }
+        );
+
+        assert_eq!(measure::<i32>(&[]), Presortedness::default());
+    }
+
+    #[test]
+    fn test_small_example() {
+        // 転倒: (3,1), (3,2), (5,4)。最長非減少部分列: 1, 2, 4
+        let v = [3, 1, 2, 5, 4];
+        assert_eq!(inversions(&v), 3);
+        assert_eq!(runs(&v), 3);
+        assert_eq!(rem(&v), 2);
+        assert_eq!(displacement(&v), 2);
+    }
+
+    #[test]
+    fn test_duplicates_are_not_inversions() {
+        let v = [2, 2, 1, 1];
+        assert_eq!(inversions(&v), 4);
+        assert_eq!(rem(&v), 2);
+        assert_eq!(runs(&[1, 1, 1]), 1);
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        for n in [1, 2, 7, 50, 300] {
+            let v = make_random_vector(n)
+                .into_iter()
+                .map(|x| x % 20)
+                .collect::<Vec<_>>();
+            assert_eq!(measure(&v), brute_force(&v), "{v:?}");
+        }
+    }
+}
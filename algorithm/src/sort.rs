@@ -1,249 +1,205 @@
+use std::fmt;
+
 use rand::seq::SliceRandom;
 
-pub struct VerifySort<'a> {
-    pub sorted: &'a mut [i32],
+/// 分布数え上げソート（比較を行わないソート）
+pub mod distribution_sort;
+/// 交換ソート
+pub mod exchange_sort;
+/// 外部ソート（メモリに収まらないファイルのソート）
+pub mod external_sort;
+/// NaN の扱いを指定した浮動小数点数のソート
+pub mod float_sort;
+/// シェルソートとコムソートの間隔列
+pub mod gap;
+/// シードを指定して再現可能な入力データを作る
+pub mod generator;
+/// 挿入ソート
+pub mod insertion_sort;
+/// マージソート
+pub mod merge_sort;
+/// 並列ソート
+pub mod parallel_sort;
+/// 全てのソートアルゴリズムの一覧
+pub mod registry;
+/// 選択アルゴリズム（k 番目の要素や上位 k 件の抽出）
+pub mod select;
+/// 選択ソート
+pub mod selection_sort;
+/// ソーティングネットワーク（奇偶マージソートとバイトニックソート）
+pub mod sorting_network;
+/// 文字列（バイト列）に特化したソート
+pub mod string_sort;
+/// ティムソート（適応型マージソート）
+pub mod tim_sort;
+/// ソート中の操作の記録と、その再生
+pub mod trace;
+
+/// ソート済みのスライスと、ソート中に行った操作の回数。
+pub struct VerifySort<'a, T> {
+    pub sorted: &'a mut [T],
+    /// 要素同士の比較回数
     pub compare_count: usize,
+    /// 要素の交換・代入回数
     pub swap_count: usize,
 }
 
-pub fn make_random_vector(count: usize) -> Vec<i32> {
-    let mut v: Vec<i32> = (0..count as i32 * 10).collect();
-    v.shuffle(&mut rand::rng());
-    v.truncate(count);
-    v
+/// 計算量のオーダー（n は要素数）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Complexity {
+    /// O(n)
+    Linear,
+    /// O(n + k)（k は値の範囲の大きさ）
+    LinearWithRange,
+    /// O(n・d)（d は桁数）
+    LinearWithDigits,
+    /// O(n log n)
+    Linearithmic,
+    /// O(n log^2 n)
+    LinearithmicSquared,
+    /// O(n^(4/3))
+    FourThirds,
+    /// O(n^(3/2))
+    ThreeHalves,
+    /// O(n^2)
+    Quadratic,
+    /// 上界が証明されていない（実験で求めた間隔列のシェルソートなど）
+    Unknown,
 }
 
-/// 交換ソート
-pub mod exchange_sort {
-    /// バブルソート (平均: O(n^2), 最悪: O(n^2))
-    /// 最後の要素から順番に確定させていく。
-    ///
-    /// 1. 0番目と1番目を比較して、順序が逆なら交換する。
-    /// 2. 1番目と2番目を比較して、順序が逆なら交換する。
-    /// 3. n-2番目とn-1番目の比較まで繰り返すと、n-1番目（最後）の要素が最大値となり確定。
-    /// 4. 0..=n-2 番目の要素について、同様に隣接する要素を比較して順序が逆なら交換する。
-    /// 5. これを繰り返して、全ての要素がソートされるまで続ける。
-    pub fn bubble(arr: &mut [impl PartialOrd]) {
-        let n = arr.len();
-
-        for i in 0..n {
-            for j in 0..n - i - 1 {
-                // 隣接する要素を比較して、順序が逆なら交換
-                if arr[j] > arr[j + 1] {
-                    arr.swap(j, j + 1);
-                }
-            }
-        }
+impl fmt::Display for Complexity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let order = match self {
+            Self::Linear => "O(n)",
+            Self::LinearWithRange => "O(n + k)",
+            Self::LinearWithDigits => "O(n・d)",
+            Self::Linearithmic => "O(n log n)",
+            Self::LinearithmicSquared => "O(n log^2 n)",
+            Self::FourThirds => "O(n^(4/3))",
+            Self::ThreeHalves => "O(n^(3/2))",
+            Self::Quadratic => "O(n^2)",
+            Self::Unknown => "unknown",
+        };
+        f.write_str(order)
     }
+}
 
-    /// シェーカーソート (平均: O(n^2), 最悪: O(n^2))
-    pub fn shaker(arr: &mut [impl PartialOrd]) {
-        let n = arr.len();
-        let mut left = 0;
-        let mut right = n - 1;
-
-        while left < right {
-            let mut swapped = false;
-            for i in left..right {
-                if arr[i] > arr[i + 1] {
-                    arr.swap(i, i + 1);
-                    swapped = true;
-                }
-            }
-            right -= 1;
+/// ソートアルゴリズムの共通のインターフェース。
+///
+/// 一覧は [`registry`] から取得できる。
+pub trait Sorter<T> {
+    /// `bench --algorithms` などで指定する名前
+    fn name(&self) -> &'static str;
 
-            for i in (left..right).rev() {
-                if arr[i] > arr[i + 1] {
-                    arr.swap(i, i + 1);
-                    swapped = true;
-                }
-            }
-            left += 1;
+    /// 等しい要素の順序を保つか
+    fn is_stable(&self) -> bool;
 
-            if !swapped {
-                break;
-            }
-        }
-    }
+    /// 要素数に比例する作業領域を使わずにソートするか
+    fn is_in_place(&self) -> bool;
 
-    /// クイックソート (平均: O(n log n), 最悪: O(n^2))
-    ///
-    /// 1. pivot（基準値）を適当（ここでは最後の要素）に選び、pivotより小さい要素群, pivot, pivotより大きい要素群の順になるように再配置する。
-    ///     - 先頭要素から順番に見ていき、pivot より大きい要素があれば、それより後に位置する pivot より小さい要素と交換する。
-    ///     - ここで pivot の最終位置が決まる。
-    /// 2. pivot より小さい要素群、pivot より大きい要素群に対して、再帰的にクイックソートを適用する。
-    pub fn quick(arr: &mut [impl PartialOrd]) {
-        if arr.len() <= 1 {
-            return;
-        }
+    /// 平均の計算量
+    fn average(&self) -> Complexity;
 
-        let pivot_index = partition(arr);
-        let (left, right) = arr.split_at_mut(pivot_index);
+    /// 最悪の計算量
+    fn worst(&self) -> Complexity;
 
-        quick(left);
-        quick(&mut right[1..]); // right[0] is the pivot, so we skip it
-    }
+    /// 計測に使う要素数の上限。`None` なら上限はない。
+    ///
+    /// これより長い入力もソートできるが、時間か作業領域が大きすぎるため `bench` では計測しない。
+    fn max_len(&self) -> Option<usize>;
 
-    fn partition(arr: &mut [impl PartialOrd]) -> usize {
-        let len = arr.len();
-        let mut i = 0;
+    /// `arr` を昇順にソートする。
+    fn sort(&self, arr: &mut [T]);
 
-        for j in 0..len - 1 {
-            // arr[len - 1] を pivot として、pivot より小さい要素を左側に集める
-            if arr[j] <= arr[len - 1] {
-                arr.swap(i, j);
-                i += 1;
-            }
-        }
-        arr.swap(i, len - 1);
-        i
+    /// `arr` を昇順にソートし、比較・交換回数を返す。
+    fn sort_counted<'a>(&self, arr: &'a mut [T]) -> VerifySort<'a, T>;
+}
+
+impl<T> fmt::Debug for dyn Sorter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
     }
 }
 
-/// 選択ソート
-pub mod selection_sort {
-    /// 選択ソート (平均: O(n^2), 最悪: O(n^2))
-    /// 0番目から順番に確定させていく。
-    ///
-    /// 1. 0..=n-1 番目の要素について最小値を見つけ、0番目の要素と交換する。
-    /// 2. 1..=n-1 番目の要素について最小値を見つけ、1番目の要素と交換する。
-    /// 3. これを繰り返す。
-    pub fn selection(arr: &mut [impl Ord]) {
-        for i in 0..arr.len() {
-            if let Some((min_index, _)) =
-                arr[i..].iter().enumerate().min_by_key(|&(_, value)| value)
-            {
-                arr.swap(i, i + min_index);
-            }
-        }
+/// ソート中の要素の操作を受け取る。
+///
+/// 各ソートは要素を比較・交換・代入するたびに対応するメソッドを呼び出す。
+/// 引数は操作した要素への参照で、交換・代入では操作後の要素を渡す。
+/// `()` は何も記録しないため、計測なしのソートでは `&mut ()` を渡す。
+pub trait Recorder<T> {
+    /// 要素 `a` と `b` を1回比較した。
+    fn compare(&mut self, _a: &T, _b: &T) {}
+
+    /// 2つの要素 `a` と `b` を交換した。
+    fn swap(&mut self, _a: &T, _b: &T) {}
+
+    /// 要素 `dst` に値を1つ代入した（挿入ソートのずらしや分布数え上げソートの書き戻しなど）。
+    fn write(&mut self, _dst: &T) {}
+
+    /// 並列ソートで、別スレッドが記録した内容を取り込む。
+    fn join(&mut self, _other: Self)
+    where
+        Self: Sized,
+    {
     }
+}
 
-    /// ヒープソート (平均: O(n log n), 最悪: O(n log n))
-    pub fn heap(arr: &mut [impl PartialOrd]) {
-        let len = arr.len();
+impl<T> Recorder<T> for () {}
 
-        // Build a max heap
-        for i in (0..len / 2).rev() {
-            sift_down(arr, i, len);
-        }
+/// 比較回数と交換・代入回数を数える [`Recorder`]。
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Counter {
+    pub compare_count: usize,
+    pub swap_count: usize,
+}
 
-        // Extract elements from heap one by one
-        for end in (1..len).rev() {
-            arr.swap(0, end);
-            sift_down(arr, 0, end);
-        }
+impl<T> Recorder<T> for Counter {
+    fn compare(&mut self, _a: &T, _b: &T) {
+        self.compare_count += 1;
     }
 
-    /// ヒープの再構築
-    fn sift_down(arr: &mut [impl PartialOrd], mut root: usize, end: usize) {
-        loop {
-            let left = 2 * root + 1;
-            let right = 2 * root + 2;
-            let mut largest = root;
-
-            if left < end && arr[left] > arr[largest] {
-                largest = left;
-            }
-            if right < end && arr[right] > arr[largest] {
-                largest = right;
-            }
-            if largest == root {
-                break;
-            }
+    fn swap(&mut self, _a: &T, _b: &T) {
+        self.swap_count += 1;
+    }
 
-            arr.swap(root, largest);
-            root = largest;
-        }
+    fn write(&mut self, _dst: &T) {
+        self.swap_count += 1;
     }
-}
 
-/// 挿入ソート
-pub mod insertion_sort {
-    /// 挿入ソート (平均: O(n^2), 最悪: O(n^2))
-    /// 0番目から基準までの要素がソート済みになる。基準を1番目から順番にしていく。
-    ///
-    /// 1. 1番目の要素を基準にして、0番目の要素と比較し、順序が逆なら交換する。
-    /// 2. 2番目の要素を基準にする。
-    ///     1. 基準と1番目の要素と比較し、順序が逆なら1番目の要素を2番目に代入する。順序が正しいなら基準の要素を2番目に代入（実質そのまま）し、2番目基準終了。
-    ///     2. 基準と0番目の要素と比較し、順序が逆なら0番目の要素を1番目に代入する。順序が正しいなら基準の要素を1番目に代入し、2番目基準終了。
-    ///     3. 基準の要素を0番目に代入し、2番目基準終了。
-    /// 3. 3番目の要素を基準にする。
-    /// 4. これを繰り返して、全ての要素がソートされるまで続ける。
-    pub fn insertion(src: &mut [impl PartialOrd + Copy]) {
-        let n = src.len();
-
-        for i in 1..n {
-            let key = src[i];
-            let mut j = i as isize - 1;
-
-            while j >= 0 && src[j as usize] > key {
-                src[(j + 1) as usize] = src[j as usize];
-                j -= 1;
-            }
-            src[(j + 1) as usize] = key;
-        }
+    fn join(&mut self, other: Self) {
+        self.compare_count += other.compare_count;
+        self.swap_count += other.swap_count;
     }
 }
 
-pub mod merge_sort {
-    use super::VerifySort;
-
-    /// マージソート (平均: O(n log n), 最悪: O(n log n))
-    /// 分割統治法を用いて、配列を再帰的に分割し、ソートされた部分配列をマージする。
-    pub fn merge(src: &mut [i32]) -> VerifySort {
-        let n = src.len();
-        let mut compare_count = 0;
-        let mut swap_count = 0;
-
-        if n <= 1 {
-            return VerifySort {
-                sorted: src,
-                compare_count,
-                swap_count,
-            };
-        }
-
-        let mid = n / 2;
-        let mut left = src[0..mid].to_vec();
-        let mut right = src[mid..n].to_vec();
-
-        let left_sort = merge(&mut left);
-        let right_sort = merge(&mut right);
-
-        compare_count += left_sort.compare_count + right_sort.compare_count;
-        swap_count += left_sort.swap_count + right_sort.swap_count;
-
-        let mut i = 0;
-        let mut j = 0;
-
-        for elem in src[..(left.len() + right.len())].iter_mut() {
-            if i < left.len() && (j >= right.len() || left[i] <= right[j]) {
-                *elem = left[i];
-                compare_count += if j >= right.len() { 2 } else { 3 };
-                i += 1;
-            } else {
-                *elem = right[j];
-                compare_count += if i < left.len() {
-                    if j >= right.len() { 3 } else { 2 }
-                } else {
-                    1
-                };
-                j += 1;
-            }
-            swap_count += 1;
-        }
+/// `sort` を [`Counter`] 付きで実行し、ソート結果と操作回数を返す。
+pub fn count<T>(arr: &mut [T], sort: impl FnOnce(&mut [T], &mut Counter)) -> VerifySort<'_, T> {
+    let mut counter = Counter::default();
+    sort(arr, &mut counter);
 
-        VerifySort {
-            sorted: src,
-            compare_count,
-            swap_count,
-        }
+    VerifySort {
+        sorted: arr,
+        compare_count: counter.compare_count,
+        swap_count: counter.swap_count,
     }
 }
 
+/// `0..count*10` から重複なく選んだ `count` 個の値をランダムな順に並べる。
+///
+/// 毎回異なる入力になる。再現が必要な場合は [`generator::Generator`] を使う。
+pub fn make_random_vector(count: usize) -> Vec<i32> {
+    let mut v: Vec<i32> = (0..count as i32 * 10).collect();
+    v.shuffle(&mut rand::rng());
+    v.truncate(count);
+    v
+}
+
 #[cfg(test)]
 mod tests {
+    use rand::Rng;
+
     use super::*;
+    use crate::check::{self, check, shrink_i32, shrink_vec};
 
     #[test]
     fn test_make_random_vector() {
@@ -254,52 +210,328 @@ mod tests {
     }
 
     #[test]
-    fn test_bubble_sort() {
-        let mut v = make_random_vector(1000);
-        exchange_sort::bubble(&mut v);
-        assert!(v.windows(2).all(|w| w[0] <= w[1]));
+    fn test_sorts() {
+        let v = make_random_vector(1000);
+        let mut expected = v.clone();
+        expected.sort();
+
+        for sorter in registry::integer_sorts() {
+            let mut w = v.clone();
+            sorter.sort(&mut w);
+            assert_eq!(w, expected, "{sorter:?}");
+        }
+    }
+
+    #[test]
+    fn test_counted_sorts() {
+        let v = make_random_vector(1000);
+
+        for sorter in registry::comparison_sorts::<i32>() {
+            let mut w = v.clone();
+            let result = sorter.sort_counted(&mut w);
+            assert!(result.sorted.windows(2).all(|w| w[0] <= w[1]), "{sorter:?}");
+            assert!(result.compare_count > 0);
+            assert!(result.swap_count > 0);
+        }
     }
 
     #[test]
-    fn test_shaker_sort() {
-        let mut v = make_random_vector(1000);
-        exchange_sort::shaker(&mut v);
-        assert!(v.windows(2).all(|w| w[0] <= w[1]));
+    fn test_counted_bubble_exact() {
+        // 逆順の4要素: 比較は 3 + 2 + 1 回、全ての比較で交換が起こる。
+        let mut v = [4, 3, 2, 1];
+        let result = exchange_sort::bubble_counted(&mut v);
+        assert_eq!(result.sorted, [1, 2, 3, 4]);
+        assert_eq!(result.compare_count, 6);
+        assert_eq!(result.swap_count, 6);
     }
 
     #[test]
-    fn test_quick_sort() {
-        let mut v = make_random_vector(1000);
-        exchange_sort::quick(&mut v);
-        assert!(v.windows(2).all(|w| w[0] <= w[1]));
+    fn test_counted_insertion_exact() {
+        // ソート済みの入力: 比較は n - 1 回で、要素は動かない。
+        let mut v = (0..100).collect::<Vec<i32>>();
+        let result = insertion_sort::insertion_counted(&mut v);
+        assert_eq!(result.compare_count, 99);
+        assert_eq!(result.swap_count, 0);
+
+        // 逆順の4要素: i 番目の基準は i 回比較し、i + 1 個の要素を代入する。
+        let mut v = [4, 3, 2, 1];
+        let result = insertion_sort::insertion_counted(&mut v);
+        assert_eq!(result.sorted, [1, 2, 3, 4]);
+        assert_eq!(result.compare_count, 6);
+        assert_eq!(result.swap_count, 9);
+    }
+
+    #[test]
+    fn test_counted_generic_element() {
+        let mut v = vec!["pear", "apple", "fig", "banana"];
+        let result = selection_sort::heap_counted(&mut v);
+        assert_eq!(result.sorted, ["apple", "banana", "fig", "pear"]);
+        assert!(result.compare_count > 0);
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Record {
+        name: String,
+        age: u32,
+    }
+
+    /// 年齢だけで比較するレコード
+    #[derive(Debug, Clone)]
+    struct ByAge(Record);
+
+    impl PartialEq for ByAge {
+        fn eq(&self, other: &Self) -> bool {
+            self.0.age == other.0.age
+        }
+    }
+
+    impl PartialOrd for ByAge {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            self.0.age.partial_cmp(&other.0.age)
+        }
+    }
+
+    fn make_records() -> Vec<Record> {
+        make_random_vector(200)
+            .into_iter()
+            .map(|x| Record {
+                name: format!("name{x}"),
+                age: (x % 50) as u32,
+            })
+            .collect()
     }
 
     #[test]
-    fn test_selection_sort() {
-        let mut v = make_random_vector(1000);
-        selection_sort::selection(&mut v);
-        assert!(v.windows(2).all(|w| w[0] <= w[1]));
+    fn test_sort_by_descending() {
+        let v = make_random_vector(1000);
+        let mut expected = v.clone();
+        expected.sort_by(|a, b| b.cmp(a));
+
+        let sorts: [fn(&mut [i32]); 10] = [
+            |v| exchange_sort::bubble_by(v, |a, b| b.cmp(a)),
+            |v| exchange_sort::shaker_by(v, |a, b| b.cmp(a)),
+            |v| exchange_sort::quick_by(v, |a, b| b.cmp(a)),
+            |v| exchange_sort::intro_by(v, |a, b| b.cmp(a)),
+            |v| selection_sort::selection_by(v, |a, b| b.cmp(a)),
+            |v| selection_sort::heap_by(v, |a, b| b.cmp(a)),
+            |v| insertion_sort::insertion_by(v, |a, b| b.cmp(a)),
+            |v| merge_sort::merge_by(v, |a, b| b.cmp(a)),
+            |v| merge_sort::merge_bottom_up_by(v, |a, b| b.cmp(a)),
+            |v| tim_sort::tim_by(v, |a, b| b.cmp(a)),
+        ];
+
+        for sort in sorts {
+            let mut w = v.clone();
+            sort(&mut w);
+            assert_eq!(w, expected);
+        }
     }
 
     #[test]
-    fn test_heap_sort() {
-        let mut v = make_random_vector(1000);
-        selection_sort::heap(&mut v);
-        assert!(v.windows(2).all(|w| w[0] <= w[1]));
+    fn test_sort_by_key_struct() {
+        let v = make_records();
+
+        let sorts: [fn(&mut [Record]); 10] = [
+            |v| exchange_sort::bubble_by_key(v, |r| r.age),
+            |v| exchange_sort::shaker_by_key(v, |r| r.age),
+            |v| exchange_sort::quick_by_key(v, |r| r.age),
+            |v| exchange_sort::intro_by_key(v, |r| r.age),
+            |v| selection_sort::selection_by_key(v, |r| r.age),
+            |v| selection_sort::heap_by_key(v, |r| r.age),
+            |v| insertion_sort::insertion_by_key(v, |r| r.age),
+            |v| merge_sort::merge_by_key(v, |r| r.age),
+            |v| merge_sort::merge_bottom_up_by_key(v, |r| r.age),
+            |v| tim_sort::tim_by_key(v, |r| r.age),
+        ];
+
+        for sort in sorts {
+            let mut w = v.clone();
+            sort(&mut w);
+            assert!(w.windows(2).all(|w| w[0].age <= w[1].age));
+        }
     }
 
     #[test]
-    fn test_insertion_sort() {
-        let mut v = make_random_vector(1000);
-        insertion_sort::insertion(&mut v);
-        assert!(v.windows(2).all(|w| w[0] <= w[1]));
+    fn test_sort_by_key_stable() {
+        // 安定ソートは標準ライブラリの sort_by_key と同じ順序になる
+        let v = make_records();
+        let mut expected = v.clone();
+        expected.sort_by_key(|r| r.age);
+
+        let sorts: [fn(&mut [Record]); 6] = [
+            |v| exchange_sort::bubble_by_key(v, |r| r.age),
+            |v| exchange_sort::shaker_by_key(v, |r| r.age),
+            |v| insertion_sort::insertion_by_key(v, |r| r.age),
+            |v| merge_sort::merge_by_key(v, |r| r.age),
+            |v| merge_sort::merge_bottom_up_by_key(v, |r| r.age),
+            |v| tim_sort::tim_by_key(v, |r| r.age),
+        ];
+
+        for sort in sorts {
+            let mut w = v.clone();
+            sort(&mut w);
+            assert_eq!(w, expected);
+        }
+    }
+
+    #[test]
+    fn test_stable_sorts() {
+        // is_stable が true のソートは、年齢の等しいレコードの順序を保つ
+        let v = make_records();
+        let mut expected = v.clone();
+        expected.sort_by_key(|r| r.age);
+
+        for sorter in registry::comparison_sorts::<ByAge>() {
+            let mut w = v.iter().cloned().map(ByAge).collect::<Vec<_>>();
+            sorter.sort(&mut w);
+            let w = w.into_iter().map(|r| r.0).collect::<Vec<_>>();
+            assert!(w.windows(2).all(|w| w[0].age <= w[1].age), "{sorter:?}");
+            if sorter.is_stable() {
+                assert_eq!(w, expected, "{sorter:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_sort_by_multiple_fields() {
+        // 年齢の降順、同じ年齢なら名前の昇順
+        let mut v = make_records();
+        let mut expected = v.clone();
+        expected.sort_by(|a, b| b.age.cmp(&a.age).then_with(|| a.name.cmp(&b.name)));
+
+        exchange_sort::quick_by(&mut v, |a, b| {
+            b.age.cmp(&a.age).then_with(|| a.name.cmp(&b.name))
+        });
+        assert_eq!(v, expected);
+    }
+
+    #[test]
+    fn test_differential_sorts() {
+        // 空・1要素・重複の多い列を含め、標準ライブラリの sort と同じ結果になる
+        for sorter in registry::integer_sorts() {
+            check(
+                sorter.name(),
+                |rng| check::vec_i32(rng, 64),
+                |v| shrink_vec(v, shrink_i32),
+                |v| {
+                    let mut expected = v.clone();
+                    expected.sort();
+
+                    let mut w = v.clone();
+                    sorter.sort(&mut w);
+                    check::equal(&expected, &w)?;
+
+                    let mut w = v.clone();
+                    let result = sorter.sort_counted(&mut w);
+                    check::equal(&expected[..], result.sorted)
+                },
+            );
+        }
+    }
+
+    /// キーだけで比較し、元の位置を持つ要素
+    #[derive(Debug, Clone, Copy)]
+    struct Keyed {
+        key: i32,
+        index: usize,
+    }
+
+    impl PartialEq for Keyed {
+        fn eq(&self, other: &Self) -> bool {
+            self.key == other.key
+        }
+    }
+
+    impl PartialOrd for Keyed {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            self.key.partial_cmp(&other.key)
+        }
+    }
+
+    #[test]
+    fn test_differential_stability() {
+        // 安定ソートは等しいキーの要素を元の位置の順に並べる
+        let stable_sorts = registry::comparison_sorts::<Keyed>()
+            .into_iter()
+            .filter(|sorter| sorter.is_stable());
+        for sorter in stable_sorts {
+            check(
+                sorter.name(),
+                |rng| check::vec_i32(rng, 64),
+                |keys| shrink_vec(keys, shrink_i32),
+                |keys| {
+                    let v = keys
+                        .iter()
+                        .enumerate()
+                        .map(|(index, &key)| Keyed { key, index })
+                        .collect::<Vec<_>>();
+                    let mut expected = v.clone();
+                    expected.sort_by_key(|x| x.key);
+
+                    let mut w = v;
+                    sorter.sort(&mut w);
+                    let order =
+                        |v: &[Keyed]| v.iter().map(|x| (x.key, x.index)).collect::<Vec<_>>();
+                    check::equal(order(&expected), order(&w))
+                },
+            );
+        }
+    }
+
+    #[test]
+    fn test_differential_string_sorts() {
+        // 共通の接頭辞や空文字列が多くなるよう、短い "ab" の文字列を使う
+        let generate = |rng: &mut rand::rngs::StdRng| {
+            let len = rng.random_range(0..=32);
+            (0..len)
+                .map(|_| {
+                    let chars = rng.random_range(0..=4);
+                    (0..chars)
+                        .map(|_| if rng.random() { 'a' } else { 'b' })
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+        };
+        let shrink_string = |s: &String| match s.len() {
+            0 => Vec::new(),
+            len => vec![String::new(), s[..len - 1].to_string()],
+        };
+
+        for sorter in registry::string_sorts() {
+            check(
+                sorter.name(),
+                generate,
+                |v| shrink_vec(v, shrink_string),
+                |v| {
+                    let mut expected = v.clone();
+                    expected.sort();
+
+                    let mut w = v.clone();
+                    sorter.sort(&mut w);
+                    check::equal(expected, w)
+                },
+            );
+        }
     }
 
     #[test]
-    fn test_merge_sort() {
-        let mut v = make_random_vector(1000);
-        let sort = merge_sort::merge(&mut v);
-        assert_eq!(sort.sorted.len(), 1000);
-        assert!(sort.sorted.windows(2).all(|w| w[0] <= w[1]));
+    fn test_differential_sorting_networks() {
+        let check_network = |name, sort: fn(&mut [i32])| {
+            check(
+                name,
+                |rng| check::vec_i32(rng, 40),
+                |v| shrink_vec(v, shrink_i32),
+                |v| {
+                    let mut expected = v.clone();
+                    expected.sort();
+
+                    let mut w = v.clone();
+                    sort(&mut w);
+                    check::equal(expected, w)
+                },
+            );
+        };
+        check_network("odd-even-merge", sorting_network::odd_even_merge);
+        check_network("bitonic", sorting_network::bitonic);
     }
 }
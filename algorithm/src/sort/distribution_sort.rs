@@ -0,0 +1,312 @@
+use super::{Recorder, VerifySort, insertion_sort};
+
+/// MSD 基数ソートでこの長さ以下のバケットは挿入ソートに切り替える
+const MSD_INSERTION_THRESHOLD: usize = 32;
+
+/// 計数ソートで確保する出現回数の配列の長さの上限は、要素数のこの倍数（ただし [`COUNTING_MIN_RANGE`] 以上）
+const COUNTING_RANGE_FACTOR: usize = 8;
+/// 計数ソートで、要素数によらず許す値の範囲
+const COUNTING_MIN_RANGE: usize = 1 << 16;
+
+/// 計数ソート (O(n + k), k は最大値と最小値の差)
+/// 要素を比較せず、各値の出現回数を数えてから値の小さい順に書き戻す。
+///
+/// 1. 最小値と最大値を求め、その間の各値の出現回数を数える。
+/// 2. 最小値から順番に、出現回数の分だけ値を書き戻す。
+///
+/// 値の範囲 k の大きさの配列を確保するため、値の範囲が要素数に比べて狭い場合に向いている。
+/// k が要素数の 8 倍（かつ 65536）を超える場合は、配列を確保せずに [`radix_lsd`] でソートする。
+pub fn counting(arr: &mut [i32]) {
+    counting_impl(arr, &mut ());
+}
+
+/// [`counting`] を実行し、代入回数を返す。比較は行わないため比較回数は 0 になる。
+pub fn counting_counted(arr: &mut [i32]) -> VerifySort<'_, i32> {
+    super::count(arr, counting_impl)
+}
+
+fn counting_impl(arr: &mut [i32], rec: &mut impl Recorder<i32>) {
+    let (Some(&min), Some(&max)) = (arr.iter().min(), arr.iter().max()) else {
+        return;
+    };
+
+    let range = (max as i64 - min as i64) as usize + 1;
+    if range > (arr.len() * COUNTING_RANGE_FACTOR).max(COUNTING_MIN_RANGE) {
+        radix_lsd_impl(arr, rec);
+        return;
+    }
+
+    let mut counts = vec![0usize; range];
+    for &x in arr.iter() {
+        counts[(x as i64 - min as i64) as usize] += 1;
+    }
+
+    let mut i = 0;
+    for (offset, &count) in counts.iter().enumerate() {
+        let value = (min as i64 + offset as i64) as i32;
+        for elem in &mut arr[i..i + count] {
+            *elem = value;
+            rec.write(elem);
+        }
+        i += count;
+    }
+}
+
+/// 符号付き整数の大小関係を保ったまま、符号なし整数に変換する（符号ビットを反転する）。
+fn radix_key(x: i32) -> u32 {
+    (x as u32) ^ 0x8000_0000
+}
+
+/// `radix_key(x)` の下から `shift` ビット目からの1バイトを取り出す。
+fn radix_digit(x: i32, shift: u32) -> usize {
+    ((radix_key(x) >> shift) & 0xff) as usize
+}
+
+/// LSD 基数ソート (O(n・d), d は桁数 = 4)
+/// 1バイトを1桁として、最下位の桁から順に安定な計数ソートを繰り返す。
+///
+/// 1. 全ての要素について、最下位バイトの値ごとの出現回数を数える。
+/// 2. 出現回数の累積和から各値の書き込み開始位置を求め、元の順序を保ったままバッファに書き込む。
+/// 3. 次のバイトについて同様に繰り返す。4バイト目まで終わるとソート済みになる。
+///
+/// 負の数は符号ビットを反転して符号なし整数として扱うことで、正の数より前に並ぶ。
+pub fn radix_lsd(arr: &mut [i32]) {
+    radix_lsd_impl(arr, &mut ());
+}
+
+/// [`radix_lsd`] を実行し、代入回数を返す。比較は行わないため比較回数は 0 になる。
+pub fn radix_lsd_counted(arr: &mut [i32]) -> VerifySort<'_, i32> {
+    super::count(arr, radix_lsd_impl)
+}
+
+fn radix_lsd_impl(arr: &mut [i32], rec: &mut impl Recorder<i32>) {
+    let mut buf = vec![0; arr.len()];
+
+    for shift in (0..32).step_by(8) {
+        let mut offsets = [0usize; 257];
+        for &x in arr.iter() {
+            offsets[radix_digit(x, shift) + 1] += 1;
+        }
+        // 全要素が同じ桁の値を持つなら、この桁の並べ替えは不要
+        if offsets.contains(&arr.len()) {
+            continue;
+        }
+        for d in 0..256 {
+            offsets[d + 1] += offsets[d];
+        }
+
+        for &x in arr.iter() {
+            let digit = radix_digit(x, shift);
+            buf[offsets[digit]] = x;
+            rec.write(&buf[offsets[digit]]);
+            offsets[digit] += 1;
+        }
+        for (elem, &x) in arr.iter_mut().zip(buf.iter()) {
+            *elem = x;
+            rec.write(elem);
+        }
+    }
+}
+
+/// MSD 基数ソート (O(n・d), d は桁数 = 4)
+/// 1バイトを1桁として、最上位の桁で分けたバケットごとに次の桁で再帰的にソートする。
+///
+/// 1. 最上位バイトの値ごとに出現回数を数え、値の順にバケットへ振り分ける。
+/// 2. 各バケットについて、次のバイトで同様に振り分ける。
+/// 3. バケットが十分小さくなったら挿入ソートで仕上げる。
+///
+/// 上位の桁で分かれた要素同士は以降の桁を見ないため、上位の桁がばらけている入力で LSD より速い。
+pub fn radix_msd(arr: &mut [i32]) {
+    radix_msd_impl(arr, &mut ());
+}
+
+/// [`radix_msd`] を実行し、比較・代入回数を返す（比較は挿入ソートに切り替えた部分のみ）。
+pub fn radix_msd_counted(arr: &mut [i32]) -> VerifySort<'_, i32> {
+    super::count(arr, radix_msd_impl)
+}
+
+fn radix_msd_impl(arr: &mut [i32], rec: &mut impl Recorder<i32>) {
+    let mut buf = vec![0; arr.len()];
+    radix_msd_rec(arr, &mut buf, 24, rec);
+}
+
+fn radix_msd_rec(arr: &mut [i32], buf: &mut [i32], shift: u32, rec: &mut impl Recorder<i32>) {
+    if arr.len() <= MSD_INSERTION_THRESHOLD {
+        insertion_sort::insertion_impl(arr, &mut i32::lt, rec);
+        return;
+    }
+
+    let mut offsets = [0usize; 257];
+    for &x in arr.iter() {
+        offsets[radix_digit(x, shift) + 1] += 1;
+    }
+    for d in 0..256 {
+        offsets[d + 1] += offsets[d];
+    }
+    let starts = offsets;
+
+    for &x in arr.iter() {
+        let digit = radix_digit(x, shift);
+        buf[offsets[digit]] = x;
+        rec.write(&buf[offsets[digit]]);
+        offsets[digit] += 1;
+    }
+    for (elem, &x) in arr.iter_mut().zip(buf.iter()) {
+        *elem = x;
+        rec.write(elem);
+    }
+
+    if shift == 0 {
+        return;
+    }
+    for d in 0..256 {
+        let (start, end) = (starts[d], starts[d + 1]);
+        if end - start > 1 {
+            radix_msd_rec(&mut arr[start..end], &mut buf[start..end], shift - 8, rec);
+        }
+    }
+}
+
+/// バケットソート (平均: O(n), 最悪: O(n^2))
+/// [0, 1) の範囲の値を n 個のバケットに振り分け、バケットごとに挿入ソートする。
+///
+/// 1. 値 x を `floor(x * n)` 番目のバケットに入れる。
+/// 2. 各バケットを挿入ソートでソートする。値が一様に分布していれば各バケットは平均1要素程度になる。
+/// 3. バケットの順に値を書き戻す。
+///
+/// # Panics
+/// [0, 1) の範囲外の値（NaN を含む）があるとパニックする。
+pub fn bucket(arr: &mut [f64]) {
+    bucket_impl(arr, &mut ());
+}
+
+/// [`bucket`] を実行し、比較・代入回数を返す（比較はバケット内の挿入ソートのみ）。
+pub fn bucket_counted(arr: &mut [f64]) -> VerifySort<'_, f64> {
+    super::count(arr, bucket_impl)
+}
+
+fn bucket_impl(arr: &mut [f64], rec: &mut impl Recorder<f64>) {
+    let n = arr.len();
+    let mut buckets = vec![Vec::new(); n];
+
+    for &x in arr.iter() {
+        assert!(
+            (0.0..1.0).contains(&x),
+            "bucket sort requires values in [0, 1), got {x}"
+        );
+        buckets[(x * n as f64) as usize].push(x);
+    }
+
+    let mut i = 0;
+    for bucket in &mut buckets {
+        insertion_sort::insertion_impl(bucket, &mut f64::lt, rec);
+        for &x in bucket.iter() {
+            arr[i] = x;
+            rec.write(&arr[i]);
+            i += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sort::make_random_vector;
+
+    /// 値の範囲が狭い入力（計数ソートでも扱える）
+    fn narrow_inputs() -> Vec<Vec<i32>> {
+        vec![
+            vec![],
+            vec![1],
+            make_random_vector(10_000),
+            make_random_vector(1000)
+                .into_iter()
+                .map(|x| x - 5000)
+                .collect(),
+            (0..1000).map(|x| x % 7 - 3).collect(),
+        ]
+    }
+
+    fn assert_sorts(sort: fn(&mut [i32]), inputs: Vec<Vec<i32>>) {
+        for v in inputs {
+            let mut expected = v.clone();
+            expected.sort();
+
+            let mut w = v.clone();
+            sort(&mut w);
+            assert_eq!(w, expected);
+        }
+    }
+
+    #[test]
+    fn test_counting() {
+        assert_sorts(counting, narrow_inputs());
+    }
+
+    #[test]
+    fn test_counting_wide_range() {
+        // 値の範囲が広い入力では、出現回数の配列を確保せずに基数ソートに切り替える
+        let inputs = vec![
+            vec![i32::MAX, i32::MIN],
+            vec![i32::MAX, 0, i32::MIN, -1, i32::MIN, 1],
+            (0..1000).map(|x| (x % 7) * 1_000_003 - 3_000_000).collect(),
+        ];
+        assert_sorts(counting, inputs);
+
+        let mut v = [i32::MAX, i32::MIN];
+        assert_eq!(counting_counted(&mut v).sorted, [i32::MIN, i32::MAX]);
+    }
+
+    #[test]
+    fn test_radix() {
+        let mut inputs = narrow_inputs();
+        inputs.push(vec![
+            i32::MAX,
+            i32::MIN,
+            0,
+            -1,
+            1,
+            i32::MIN + 1,
+            i32::MAX - 1,
+        ]);
+        inputs.push((0..1000).map(|x| (x % 7) * 1_000_003 - 3_000_000).collect());
+        inputs.push(
+            make_random_vector(1000)
+                .into_iter()
+                .map(|x| x.wrapping_mul(-2_654_435))
+                .collect(),
+        );
+
+        for sort in [radix_lsd, radix_msd] {
+            assert_sorts(sort, inputs.clone());
+        }
+    }
+
+    #[test]
+    fn test_radix_counted_has_no_comparisons() {
+        let mut v = make_random_vector(1000);
+        let result = radix_lsd_counted(&mut v);
+        assert_eq!(result.compare_count, 0);
+        assert!(result.sorted.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn test_bucket() {
+        let n = 10_000;
+        let mut v = make_random_vector(n)
+            .into_iter()
+            .map(|x| x as f64 / (n * 10) as f64)
+            .collect::<Vec<_>>();
+        let mut expected = v.clone();
+        expected.sort_by(f64::total_cmp);
+
+        bucket(&mut v);
+        assert_eq!(v, expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bucket_out_of_range() {
+        bucket(&mut [0.5, 1.0]);
+    }
+}
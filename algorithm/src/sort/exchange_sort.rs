@@ -0,0 +1,559 @@
+use std::cmp::Ordering;
+
+use super::gap::GapSequence;
+use super::trace::{self, Trace};
+use super::{Recorder, VerifySort, insertion_sort, selection_sort};
+
+/// バブルソート (平均: O(n^2), 最悪: O(n^2))
+/// 最後の要素から順番に確定させていく。
+///
+/// 1. 0番目と1番目を比較して、順序が逆なら交換する。
+/// 2. 1番目と2番目を比較して、順序が逆なら交換する。
+/// 3. n-2番目とn-1番目の比較まで繰り返すと、n-1番目（最後）の要素が最大値となり確定。
+/// 4. 0..=n-2 番目の要素について、同様に隣接する要素を比較して順序が逆なら交換する。
+/// 5. これを繰り返して、全ての要素がソートされるまで続ける。
+pub fn bubble<T: PartialOrd>(arr: &mut [T]) {
+    bubble_impl(arr, &mut T::lt, &mut ());
+}
+
+/// 比較関数 `compare` を用いて [`bubble`] を行う。
+pub fn bubble_by<T, F>(arr: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    bubble_impl(arr, &mut |a, b| compare(a, b) == Ordering::Less, &mut ());
+}
+
+/// キー抽出関数 `f` が返すキーの順に [`bubble`] を行う。
+pub fn bubble_by_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    bubble_impl(arr, &mut |a, b| f(a).lt(&f(b)), &mut ());
+}
+
+/// [`bubble`] を実行し、比較・交換回数を返す。
+pub fn bubble_counted<T: PartialOrd>(arr: &mut [T]) -> VerifySort<'_, T> {
+    super::count(arr, |arr, rec| bubble_impl(arr, &mut T::lt, rec))
+}
+
+/// [`bubble`] を実行し、比較・交換の記録を返す。
+pub fn bubble_traced<T: PartialOrd + Clone>(arr: &mut [T]) -> Trace<T> {
+    trace::trace(arr, |arr, rec| bubble_impl(arr, &mut T::lt, rec))
+}
+
+fn bubble_impl<T, F, R>(arr: &mut [T], is_less: &mut F, rec: &mut R)
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    let n = arr.len();
+
+    for i in 0..n {
+        for j in 0..n - i - 1 {
+            // 隣接する要素を比較して、順序が逆なら交換
+            rec.compare(&arr[j + 1], &arr[j]);
+            if is_less(&arr[j + 1], &arr[j]) {
+                arr.swap(j, j + 1);
+                rec.swap(&arr[j], &arr[j + 1]);
+            }
+        }
+    }
+}
+
+/// シェーカーソート (平均: O(n^2), 最悪: O(n^2))
+pub fn shaker<T: PartialOrd>(arr: &mut [T]) {
+    shaker_impl(arr, &mut T::lt, &mut ());
+}
+
+/// 比較関数 `compare` を用いて [`shaker`] を行う。
+pub fn shaker_by<T, F>(arr: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    shaker_impl(arr, &mut |a, b| compare(a, b) == Ordering::Less, &mut ());
+}
+
+/// キー抽出関数 `f` が返すキーの順に [`shaker`] を行う。
+pub fn shaker_by_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    shaker_impl(arr, &mut |a, b| f(a).lt(&f(b)), &mut ());
+}
+
+/// [`shaker`] を実行し、比較・交換回数を返す。
+pub fn shaker_counted<T: PartialOrd>(arr: &mut [T]) -> VerifySort<'_, T> {
+    super::count(arr, |arr, rec| shaker_impl(arr, &mut T::lt, rec))
+}
+
+/// [`shaker`] を実行し、比較・交換の記録を返す。
+pub fn shaker_traced<T: PartialOrd + Clone>(arr: &mut [T]) -> Trace<T> {
+    trace::trace(arr, |arr, rec| shaker_impl(arr, &mut T::lt, rec))
+}
+
+fn shaker_impl<T, F, R>(arr: &mut [T], is_less: &mut F, rec: &mut R)
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    // 空のスライスでは right が 0 になり、ループに入らない
+    let mut left = 0;
+    let mut right = arr.len().saturating_sub(1);
+
+    while left < right {
+        let mut swapped = false;
+        for i in left..right {
+            rec.compare(&arr[i + 1], &arr[i]);
+            if is_less(&arr[i + 1], &arr[i]) {
+                arr.swap(i, i + 1);
+                rec.swap(&arr[i], &arr[i + 1]);
+                swapped = true;
+            }
+        }
+        right -= 1;
+
+        for i in (left..right).rev() {
+            rec.compare(&arr[i + 1], &arr[i]);
+            if is_less(&arr[i + 1], &arr[i]) {
+                arr.swap(i, i + 1);
+                rec.swap(&arr[i], &arr[i + 1]);
+                swapped = true;
+            }
+        }
+        left += 1;
+
+        if !swapped {
+            break;
+        }
+    }
+}
+
+/// コムソート (平均: 間隔列による, 最悪: O(n^2))
+/// バブルソートで隣接する要素の代わりに離れた要素を比較・交換し、
+/// 末尾付近の小さい要素（亀）を少ない交換で前へ運ぶ。
+///
+/// 1. 間隔列 `gaps` の最も大きい間隔 h を選び、h 離れた要素同士を先頭から順に比較して、順序が逆なら交換する。
+/// 2. 間隔を次に大きいものにして、同様に1回走査する。
+/// 3. 間隔が 1 になったら、交換が起きなくなるまでバブルソートと同じ走査を繰り返す。
+///
+/// 各間隔で1回しか走査しないため、シェルソート向けの間隔列（隣の間隔との比が 2 以上）では
+/// 間隔 1 の走査が多く残り、O(n^2) に近くなる。
+pub fn comb<T: PartialOrd>(arr: &mut [T], gaps: GapSequence) {
+    comb_impl(arr, gaps, &mut T::lt, &mut ());
+}
+
+/// 比較関数 `compare` を用いて [`comb`] を行う。
+pub fn comb_by<T, F>(arr: &mut [T], gaps: GapSequence, mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    comb_impl(
+        arr,
+        gaps,
+        &mut |a, b| compare(a, b) == Ordering::Less,
+        &mut (),
+    );
+}
+
+/// キー抽出関数 `f` が返すキーの順に [`comb`] を行う。
+pub fn comb_by_key<T, K, F>(arr: &mut [T], gaps: GapSequence, mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    comb_impl(arr, gaps, &mut |a, b| f(a).lt(&f(b)), &mut ());
+}
+
+/// [`comb`] を実行し、比較・交換回数を返す。
+pub fn comb_counted<T: PartialOrd>(arr: &mut [T], gaps: GapSequence) -> VerifySort<'_, T> {
+    super::count(arr, |arr, rec| comb_impl(arr, gaps, &mut T::lt, rec))
+}
+
+fn comb_impl<T, F, R>(arr: &mut [T], gaps: GapSequence, is_less: &mut F, rec: &mut R)
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    let mut swapped = false;
+    for gap in gaps.gaps(arr.len()) {
+        swapped = comb_pass(arr, gap, is_less, rec);
+    }
+    // 間隔 1 の走査はバブルソートと同じ
+    while swapped {
+        swapped = comb_pass(arr, 1, is_less, rec);
+    }
+}
+
+/// `gap` 離れた要素同士を先頭から順に比較・交換し、交換したかどうかを返す。
+fn comb_pass<T, F, R>(arr: &mut [T], gap: usize, is_less: &mut F, rec: &mut R) -> bool
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    let mut swapped = false;
+    for i in 0..arr.len() - gap {
+        rec.compare(&arr[i + gap], &arr[i]);
+        if is_less(&arr[i + gap], &arr[i]) {
+            arr.swap(i, i + gap);
+            rec.swap(&arr[i], &arr[i + gap]);
+            swapped = true;
+        }
+    }
+    swapped
+}
+
+/// クイックソート (平均: O(n log n), 最悪: O(n log n))
+///
+/// 1. pivot（基準値）を3点の中央値（長い区間では ninther）で選んで最後の要素と交換し、
+///    pivotより小さい要素群, pivot, pivotより大きい要素群の順になるように再配置する。
+///     - 先頭要素から順番に見ていき、pivot より大きい要素があれば、それより後に位置する pivot より小さい要素と交換する。
+///     - ここで pivot の最終位置が決まる。
+/// 2. pivot より小さい要素群、pivot より大きい要素群のうち、短い方を再帰で、長い方をループでソートする（スタックの深さは O(log n)）。
+/// 3. 分割の深さが 2・log n を超えたら、その区間はヒープソートに切り替える（等しい要素ばかりの入力でも O(n log n)）。
+pub fn quick<T: PartialOrd>(arr: &mut [T]) {
+    quick_impl(arr, &mut T::lt, &mut ());
+}
+
+/// 比較関数 `compare` を用いて [`quick`] を行う。
+pub fn quick_by<T, F>(arr: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    quick_impl(arr, &mut |a, b| compare(a, b) == Ordering::Less, &mut ());
+}
+
+/// キー抽出関数 `f` が返すキーの順に [`quick`] を行う。
+pub fn quick_by_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    quick_impl(arr, &mut |a, b| f(a).lt(&f(b)), &mut ());
+}
+
+/// [`quick`] を実行し、比較・交換回数を返す。
+pub fn quick_counted<T: PartialOrd>(arr: &mut [T]) -> VerifySort<'_, T> {
+    super::count(arr, |arr, rec| quick_impl(arr, &mut T::lt, rec))
+}
+
+/// [`quick`] を実行し、比較・交換の記録を返す。
+pub fn quick_traced<T: PartialOrd + Clone>(arr: &mut [T]) -> Trace<T> {
+    trace::trace(arr, |arr, rec| quick_impl(arr, &mut T::lt, rec))
+}
+
+fn quick_impl<T, F, R>(arr: &mut [T], is_less: &mut F, rec: &mut R)
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    let depth_limit = 2 * arr.len().max(1).ilog2();
+    quick_loop(arr, depth_limit, is_less, rec);
+}
+
+fn quick_loop<T, F, R>(mut arr: &mut [T], mut depth_limit: u32, is_less: &mut F, rec: &mut R)
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    while arr.len() > 1 {
+        if depth_limit == 0 {
+            selection_sort::heap_impl(arr, is_less, rec);
+            return;
+        }
+        depth_limit -= 1;
+
+        let len = arr.len();
+        let pivot_index = choose_pivot(arr, is_less, rec);
+        if pivot_index != len - 1 {
+            arr.swap(pivot_index, len - 1);
+            rec.swap(&arr[pivot_index], &arr[len - 1]);
+        }
+        let pivot_index = partition(arr, is_less, rec);
+
+        // 短い方を再帰でソートし、長い方は次のループでソートする
+        let (left, right) = arr.split_at_mut(pivot_index);
+        let right = &mut right[1..]; // right[0] is the pivot, so we skip it
+        if left.len() < right.len() {
+            quick_loop(left, depth_limit, is_less, rec);
+            arr = right;
+        } else {
+            quick_loop(right, depth_limit, is_less, rec);
+            arr = left;
+        }
+    }
+}
+
+pub(super) fn partition<T, F, R>(arr: &mut [T], is_less: &mut F, rec: &mut R) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    let len = arr.len();
+    let mut i = 0;
+
+    for j in 0..len - 1 {
+        // arr[len - 1] を pivot として、pivot より小さい要素を左側に集める
+        rec.compare(&arr[len - 1], &arr[j]);
+        if !is_less(&arr[len - 1], &arr[j]) {
+            arr.swap(i, j);
+            rec.swap(&arr[i], &arr[j]);
+            i += 1;
+        }
+    }
+    arr.swap(i, len - 1);
+    rec.swap(&arr[i], &arr[len - 1]);
+    i
+}
+
+/// この長さ以下の区間はイントロソートで挿入ソートに切り替える
+const INSERTION_THRESHOLD: usize = 16;
+/// この長さ以上の区間は pivot を ninther（3つの3点中央値の中央値）で選ぶ
+const NINTHER_THRESHOLD: usize = 128;
+
+/// イントロソート (平均: O(n log n), 最悪: O(n log n))
+/// クイックソートを基本として、苦手な入力を他のソートで補う。
+///
+/// 1. 区間が短ければ（16要素以下）、挿入ソートで仕上げる。
+/// 2. 再帰の深さが 2・log n を超えたら、その区間はヒープソートに切り替える（最悪 O(n log n) を保証）。
+/// 3. pivot を3点の中央値（長い区間では ninther）で選ぶ。ソート済み・逆順の入力でも偏りにくい。
+/// 4. pivot より小さい要素群、pivot と等しい要素群、pivot より大きい要素群の3つに分割する（三分割）。
+///     - pivot と等しい要素群は確定するため、重複の多い入力で速い。
+/// 5. 小さい要素群・大きい要素群のうち短い方を再帰で、長い方をループでソートする（スタックの深さは O(log n)）。
+pub fn intro<T: PartialOrd>(arr: &mut [T]) {
+    intro_impl(arr, &mut T::lt, &mut ());
+}
+
+/// 比較関数 `compare` を用いて [`intro`] を行う。
+pub fn intro_by<T, F>(arr: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    intro_impl(arr, &mut |a, b| compare(a, b) == Ordering::Less, &mut ());
+}
+
+/// キー抽出関数 `f` が返すキーの順に [`intro`] を行う。
+pub fn intro_by_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    intro_impl(arr, &mut |a, b| f(a).lt(&f(b)), &mut ());
+}
+
+/// [`intro`] を実行し、比較・交換回数を返す。
+pub fn intro_counted<T: PartialOrd>(arr: &mut [T]) -> VerifySort<'_, T> {
+    super::count(arr, |arr, rec| intro_impl(arr, &mut T::lt, rec))
+}
+
+pub(super) fn intro_impl<T, F, R>(arr: &mut [T], is_less: &mut F, rec: &mut R)
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    let depth_limit = 2 * arr.len().max(1).ilog2();
+    intro_loop(arr, depth_limit, is_less, rec);
+}
+
+fn intro_loop<T, F, R>(mut arr: &mut [T], mut depth_limit: u32, is_less: &mut F, rec: &mut R)
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    loop {
+        if arr.len() <= INSERTION_THRESHOLD {
+            insertion_sort::insertion_impl(arr, is_less, rec);
+            return;
+        }
+        if depth_limit == 0 {
+            selection_sort::heap_impl(arr, is_less, rec);
+            return;
+        }
+        depth_limit -= 1;
+
+        let pivot_index = choose_pivot(arr, is_less, rec);
+        let (lt, gt) = partition3(arr, pivot_index, is_less, rec);
+
+        // 短い方を再帰でソートし、長い方は次のループでソートする
+        let (left, rest) = arr.split_at_mut(lt);
+        let right = &mut rest[gt - lt..];
+        if left.len() < right.len() {
+            intro_loop(left, depth_limit, is_less, rec);
+            arr = right;
+        } else {
+            intro_loop(right, depth_limit, is_less, rec);
+            arr = left;
+        }
+    }
+}
+
+/// pivot のインデックスを選ぶ。短い区間では3点の中央値、長い区間では ninther を使う。
+pub(super) fn choose_pivot<T, F, R>(arr: &[T], is_less: &mut F, rec: &mut R) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    let len = arr.len();
+    let (a, b, c) = (0, len / 2, len - 1);
+
+    if len < NINTHER_THRESHOLD {
+        return median3(arr, a, b, c, is_less, rec);
+    }
+
+    let step = len / 8;
+    let a = median3(arr, a, a + step, a + 2 * step, is_less, rec);
+    let b = median3(arr, b - step, b, b + step, is_less, rec);
+    let c = median3(arr, c - 2 * step, c - step, c, is_less, rec);
+    median3(arr, a, b, c, is_less, rec)
+}
+
+/// `arr[a]`, `arr[b]`, `arr[c]` のうち中央値となる要素のインデックスを返す。
+fn median3<T, F, R>(arr: &[T], a: usize, b: usize, c: usize, is_less: &mut F, rec: &mut R) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    let mut less = |x: usize, y: usize| {
+        rec.compare(&arr[x], &arr[y]);
+        is_less(&arr[x], &arr[y])
+    };
+
+    if less(a, b) {
+        if less(b, c) {
+            b
+        } else if less(a, c) {
+            c
+        } else {
+            a
+        }
+    } else if less(a, c) {
+        a
+    } else if less(b, c) {
+        c
+    } else {
+        b
+    }
+}
+
+/// `arr[pivot_index]` を pivot として三分割し、pivot と等しい要素群の範囲 `(lt, gt)` を返す。
+///
+/// 分割後は `arr[..lt]` が pivot より小さく、`arr[lt..gt]` が pivot と等しく、`arr[gt..]` が pivot より大きい。
+/// 走査中は `arr[lt]` が常に pivot と等しい要素になるため、これを pivot として比較する。
+pub(super) fn partition3<T, F, R>(
+    arr: &mut [T],
+    pivot_index: usize,
+    is_less: &mut F,
+    rec: &mut R,
+) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    arr.swap(0, pivot_index);
+    rec.swap(&arr[0], &arr[pivot_index]);
+
+    let mut lt = 0;
+    let mut i = 1;
+    let mut gt = arr.len();
+
+    while i < gt {
+        rec.compare(&arr[i], &arr[lt]);
+        if is_less(&arr[i], &arr[lt]) {
+            arr.swap(lt, i);
+            rec.swap(&arr[lt], &arr[i]);
+            lt += 1;
+            i += 1;
+            continue;
+        }
+
+        rec.compare(&arr[lt], &arr[i]);
+        if is_less(&arr[lt], &arr[i]) {
+            gt -= 1;
+            arr.swap(i, gt);
+            rec.swap(&arr[i], &arr[gt]);
+        } else {
+            i += 1;
+        }
+    }
+
+    (lt, gt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_sorted(v: &[i32]) -> bool {
+        v.windows(2).all(|w| w[0] <= w[1])
+    }
+
+    #[test]
+    fn test_intro_inputs() {
+        let n = 10_000;
+        let inputs = [
+            (0..n).collect::<Vec<_>>(),
+            (0..n).rev().collect(),
+            (0..n).map(|x| x % 3).collect(),
+            vec![7; n as usize],
+            crate::sort::make_random_vector(n as usize),
+        ];
+
+        for mut v in inputs {
+            intro(&mut v);
+            assert!(is_sorted(&v));
+        }
+    }
+
+    #[test]
+    fn test_intro_short() {
+        for n in 0..40 {
+            let mut v = (0..n).rev().collect::<Vec<i32>>();
+            intro(&mut v);
+            assert!(is_sorted(&v));
+        }
+    }
+
+    #[test]
+    fn test_intro_sorted_input_is_not_quadratic() {
+        // pivot を中央値で選ぶため、ソート済みの入力でも n^2 / 2 回も比較しない
+        let n = 4096;
+        let mut sorted = (0..n).collect::<Vec<i32>>();
+        let quick = quick_counted(&mut sorted);
+        assert!(quick.compare_count < 4 * n as usize * n.ilog2() as usize);
+
+        let intro = intro_counted(&mut sorted);
+        assert!(intro.compare_count < 4 * n as usize * n.ilog2() as usize);
+    }
+
+    #[test]
+    fn test_quick_large_degenerate_inputs() {
+        // 以前は最後の要素を pivot にして分割の片側だけを再帰したため、スタックが溢れていた
+        let n = 200_000;
+        let inputs = [
+            (0..n).collect::<Vec<i32>>(),
+            (0..n).rev().collect(),
+            vec![7; n as usize],
+            (0..n).map(|x| x % 2).collect(),
+        ];
+
+        for mut v in inputs {
+            quick(&mut v);
+            assert!(is_sorted(&v));
+        }
+
+        // 等しい要素ばかりの入力では分割が偏るが、ヒープソートに切り替わる
+        let mut equal = vec![7; 4096];
+        let result = quick_counted(&mut equal);
+        assert!(result.compare_count < 8 * 4096 * 12);
+    }
+
+    #[test]
+    fn test_intro_heap_fallback() {
+        // 深さの上限が 0 でもヒープソートに切り替わってソートできる
+        let mut v = crate::sort::make_random_vector(1000);
+        intro_loop(&mut v, 0, &mut i32::lt, &mut ());
+        assert!(is_sorted(&v));
+    }
+}
@@ -0,0 +1,428 @@
+use std::cell::Cell;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::tim_sort;
+
+/// マージで1つのランの読み込みに使うバッファの大きさの下限（バイト）
+const MIN_MERGE_BUFFER: usize = 64;
+
+/// 外部ソートの設定。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalConfig {
+    /// 1つのチャンクに読み込むデータ量の上限（バイト）。
+    /// 各レコードの大きさは `size_of::<T>()` と行のバイト数の和で見積もる。
+    /// マージでは、各ランの読み込みバッファと出力のバッファをこの大きさで等分する。
+    pub memory_budget: usize,
+    /// 一度にマージするランの数の上限。ランがこれより多い場合は、複数回に分けてマージする。
+    /// 各ランのバッファが `memory_budget` に収まらないほど多い場合は、収まる数に減らす。
+    pub fan_in: usize,
+    /// ランを書き出す一時ディレクトリを作る場所
+    pub temp_dir: PathBuf,
+}
+
+impl Default for ExternalConfig {
+    /// メモリ 64 MiB、マージ数 64、一時ディレクトリは OS の既定の場所とする。
+    fn default() -> Self {
+        Self {
+            memory_budget: 64 * 1024 * 1024,
+            fan_in: 64,
+            temp_dir: std::env::temp_dir(),
+        }
+    }
+}
+
+/// 外部ソートの実行結果。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExternalSortStats {
+    /// ソートしたレコード数
+    pub records: usize,
+    /// 最初に書き出したソート済みランの数
+    pub runs: usize,
+    /// ランのマージを行った回数（最後の出力ファイルへのマージを含む）
+    pub merge_passes: usize,
+}
+
+/// 外部ソート
+/// メモリに収まらない大きさのファイルを、ソート済みの一時ファイル（ラン）に分けてからマージする。
+///
+/// 1. 入力ファイルを1行1レコードとして、`memory_budget` に収まる分だけ読み込む（チャンク）。
+/// 2. チャンクを [`tim_sort::tim`] でソートし、ランとして一時ファイルに書き出す。入力が尽きるまで繰り返す。
+/// 3. ランが `fan_in` 個より多ければ、`fan_in` 個ずつマージして新しいランにする。
+/// 4. 全てのランの先頭のレコードを `BinaryHeap` に入れ、最小のものから順に出力ファイルへ書き出す（k-way マージ）。
+///
+/// マージでは、`fan_in` 個のランと出力のバッファで `memory_budget` を等分する。
+/// 1つのバッファが [`MIN_MERGE_BUFFER`] を下回る場合は、`fan_in` を減らしてマージの回数を増やす。
+///
+/// 各行は `T::from_str` で読み込み、`Display` で書き出す。行全体で並べるなら `T = String` を使う。
+/// 等しいレコードは入力での順序を保つ（安定）。一時ファイルは終了時に削除する。
+///
+/// # Errors
+/// 入出力に失敗した場合と、行を `T` として読み込めなかった場合（`InvalidData`）にエラーを返す。
+/// マージで一時ファイルの行を読み込めなかった場合（`Display` で書いた行を `FromStr` で読めない場合）は、
+/// ランと行の番号をエラーに含める。
+pub fn sort_file<T>(
+    input: &Path,
+    output: &Path,
+    config: &ExternalConfig,
+) -> io::Result<ExternalSortStats>
+where
+    T: FromStr + Display + Ord + Clone,
+    T::Err: Display,
+{
+    let temp = TempDir::new(&config.temp_dir)?;
+    let mut reader = BufReader::new(File::open(input)?);
+    let mut stats = ExternalSortStats {
+        records: 0,
+        runs: 0,
+        merge_passes: 0,
+    };
+
+    let mut runs = Vec::new();
+    let mut chunk = Vec::new();
+    let mut chunk_bytes = 0;
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        let text = line.strip_suffix('\n').unwrap_or(&line);
+        let text = text.strip_suffix('\r').unwrap_or(text);
+        let record = text.parse::<T>().map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {e}", stats.records + 1),
+            )
+        })?;
+
+        chunk_bytes += mem::size_of::<T>() + text.len();
+        chunk.push(record);
+        stats.records += 1;
+
+        if chunk_bytes >= config.memory_budget {
+            runs.push(write_run(&temp, &mut chunk)?);
+            chunk_bytes = 0;
+        }
+    }
+
+    // 全体が1つのチャンクに収まった場合は、一時ファイルを経由せずに書き出す
+    if runs.is_empty() {
+        tim_sort::tim(&mut chunk);
+        write_records(&mut BufWriter::new(File::create(output)?), &chunk)?;
+        return Ok(stats);
+    }
+    if !chunk.is_empty() {
+        runs.push(write_run(&temp, &mut chunk)?);
+    }
+    drop(chunk);
+    stats.runs = runs.len();
+
+    // fan_in 個のランの読み込みと出力の1つで、メモリを等分する
+    let max_fan_in = (config.memory_budget / MIN_MERGE_BUFFER).saturating_sub(1);
+    let fan_in = config.fan_in.min(max_fan_in).max(2);
+    let buffer_size = (config.memory_budget / (fan_in + 1)).max(MIN_MERGE_BUFFER);
+    while runs.len() > fan_in {
+        let mut merged = Vec::new();
+        for group in runs.chunks(fan_in) {
+            let path = temp.next_path();
+            merge_runs::<T>(group, &path, buffer_size)?;
+            merged.push(path);
+        }
+        for path in runs {
+            fs::remove_file(path)?;
+        }
+        runs = merged;
+        stats.merge_passes += 1;
+    }
+
+    merge_runs::<T>(&runs, output, buffer_size)?;
+    stats.merge_passes += 1;
+
+    Ok(stats)
+}
+
+/// チャンクをソートしてランとして書き出し、チャンクを空にする。
+fn write_run<T>(temp: &TempDir, chunk: &mut Vec<T>) -> io::Result<PathBuf>
+where
+    T: Display + Ord + Clone,
+{
+    tim_sort::tim(chunk);
+
+    let path = temp.next_path();
+    write_records(&mut BufWriter::new(File::create(&path)?), chunk)?;
+    chunk.clear();
+    Ok(path)
+}
+
+fn write_records<T: Display>(writer: &mut impl Write, records: &[T]) -> io::Result<()> {
+    for record in records {
+        writeln!(writer, "{record}")?;
+    }
+    writer.flush()
+}
+
+/// ソート済みのラン `runs` を k-way マージして `output` に書き出す。
+/// 各ランの読み込みと出力には、それぞれ `buffer_size` バイトのバッファを使う。
+///
+/// ヒープには (レコード, ランの番号) を入れるので、等しいレコードは前のランのものが先に出る。
+fn merge_runs<T>(runs: &[PathBuf], output: &Path, buffer_size: usize) -> io::Result<()>
+where
+    T: FromStr + Display + Ord,
+    T::Err: Display,
+{
+    let mut readers = runs
+        .iter()
+        .map(|path| {
+            File::open(path).map(|file| BufReader::with_capacity(buffer_size, file).lines())
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    let mut writer = BufWriter::with_capacity(buffer_size, File::create(output)?);
+    // 各ランで読み込んだ行数
+    let mut line_counts = vec![0; runs.len()];
+
+    let mut next = |i: usize| -> io::Result<Option<T>> {
+        let Some(line) = readers[i].next() else {
+            return Ok(None);
+        };
+        line_counts[i] += 1;
+        line?.parse().map(Some).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("run {} line {}: {e}", runs[i].display(), line_counts[i]),
+            )
+        })
+    };
+
+    let mut heap = BinaryHeap::with_capacity(runs.len());
+    for i in 0..runs.len() {
+        if let Some(record) = next(i)? {
+            heap.push(Reverse((record, i)));
+        }
+    }
+
+    while let Some(Reverse((record, i))) = heap.pop() {
+        writeln!(writer, "{record}")?;
+        if let Some(record) = next(i)? {
+            heap.push(Reverse((record, i)));
+        }
+    }
+
+    writer.flush()
+}
+
+/// ランを置く一時ディレクトリ。破棄時に中身ごと削除する。
+struct TempDir {
+    path: PathBuf,
+    count: Cell<usize>,
+}
+
+impl TempDir {
+    fn new(parent: &Path) -> io::Result<Self> {
+        // 同じプロセス内で同時に作っても名前が重ならないように、連番も付ける
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos());
+        let path = parent.join(format!("external-sort-{}-{nanos}-{id}", process::id()));
+        fs::create_dir_all(&path)?;
+
+        Ok(Self {
+            path,
+            count: Cell::new(0),
+        })
+    }
+
+    /// まだ使っていない一時ファイルのパスを返す。
+    fn next_path(&self) -> PathBuf {
+        let count = self.count.get();
+        self.count.set(count + 1);
+        self.path.join(format!("run-{count}.txt"))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sort::make_random_vector;
+
+    /// 入出力ファイルを置く作業ディレクトリ
+    fn work_dir() -> TempDir {
+        TempDir::new(&std::env::temp_dir()).unwrap()
+    }
+
+    fn write_lines<T: Display>(path: &Path, records: &[T]) {
+        write_records(&mut BufWriter::new(File::create(path).unwrap()), records).unwrap();
+    }
+
+    fn read_lines(path: &Path) -> Vec<String> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn test_sort_integers() {
+        let dir = work_dir();
+        let (input, output) = (dir.next_path(), dir.next_path());
+        let v = make_random_vector(10_000)
+            .into_iter()
+            .map(|x| x as i64 - 50_000)
+            .collect::<Vec<_>>();
+        write_lines(&input, &v);
+
+        let config = ExternalConfig {
+            memory_budget: 4096,
+            fan_in: 8,
+            temp_dir: dir.path.clone(),
+        };
+        let stats = sort_file::<i64>(&input, &output, &config).unwrap();
+
+        let mut expected = v;
+        expected.sort();
+        let sorted = read_lines(&output)
+            .iter()
+            .map(|line| line.parse::<i64>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(sorted, expected);
+        assert_eq!(stats.records, 10_000);
+        assert!(stats.runs > 8);
+        assert!(stats.merge_passes > 1);
+    }
+
+    #[test]
+    fn test_sort_lines() {
+        let dir = work_dir();
+        let (input, output) = (dir.next_path(), dir.next_path());
+        let v = make_random_vector(2000)
+            .into_iter()
+            .map(|x| format!("user{x} action{}", x % 7))
+            .collect::<Vec<_>>();
+        write_lines(&input, &v);
+
+        let config = ExternalConfig {
+            memory_budget: 1024,
+            temp_dir: dir.path.clone(),
+            ..Default::default()
+        };
+        sort_file::<String>(&input, &output, &config).unwrap();
+
+        let mut expected = v;
+        expected.sort();
+        assert_eq!(read_lines(&output), expected);
+    }
+
+    #[test]
+    fn test_sort_fits_in_memory() {
+        let dir = work_dir();
+        let (input, output) = (dir.next_path(), dir.next_path());
+        write_lines(&input, &[3, 1, 2]);
+
+        let config = ExternalConfig {
+            temp_dir: dir.path.clone(),
+            ..Default::default()
+        };
+        let stats = sort_file::<i32>(&input, &output, &config).unwrap();
+        assert_eq!(read_lines(&output), ["1", "2", "3"]);
+        assert_eq!(stats.runs, 0);
+        assert_eq!(stats.merge_passes, 0);
+    }
+
+    #[test]
+    fn test_merge_within_memory_budget() {
+        let dir = work_dir();
+        let (input, output) = (dir.next_path(), dir.next_path());
+        let v = make_random_vector(5000);
+        write_lines(&input, &v);
+
+        // 64 個のランのバッファは 1024 バイトに収まらないので、マージする数を減らす
+        let small = ExternalConfig {
+            memory_budget: 1024,
+            fan_in: 64,
+            temp_dir: dir.path.clone(),
+        };
+        let stats = sort_file::<i32>(&input, &output, &small).unwrap();
+        assert!(stats.runs > 1024 / MIN_MERGE_BUFFER);
+        assert!(stats.merge_passes > 1);
+
+        let mut expected = v;
+        expected.sort();
+        let sorted = read_lines(&output)
+            .iter()
+            .map(|line| line.parse::<i32>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(sorted, expected);
+    }
+
+    #[test]
+    fn test_merge_invalid_line() {
+        // 書き出した形式を読み込めないレコード
+        #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+        struct Tagged(i32);
+
+        impl FromStr for Tagged {
+            type Err = std::num::ParseIntError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                s.parse().map(Self)
+            }
+        }
+
+        impl Display for Tagged {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "#{}", self.0)
+            }
+        }
+
+        let dir = work_dir();
+        let (input, output) = (dir.next_path(), dir.next_path());
+        write_lines(&input, &make_random_vector(1000));
+
+        let config = ExternalConfig {
+            memory_budget: 1024,
+            temp_dir: dir.path.clone(),
+            ..Default::default()
+        };
+        let err = sort_file::<Tagged>(&input, &output, &config).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let message = err.to_string();
+        assert!(
+            message.starts_with("run ") && message.contains("run-0.txt line 1:"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn test_sort_invalid_line() {
+        let dir = work_dir();
+        let (input, output) = (dir.next_path(), dir.next_path());
+        fs::write(&input, "1\n2\nthree\n").unwrap();
+
+        let config = ExternalConfig {
+            temp_dir: dir.path.clone(),
+            ..Default::default()
+        };
+        let err = sort_file::<i32>(&input, &output, &config).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().starts_with("line 3:"));
+    }
+}
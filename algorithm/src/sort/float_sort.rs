@@ -0,0 +1,186 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+
+use super::{VerifySort, tim_sort};
+
+/// NaN をどこに並べるか
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NanPolicy {
+    /// 全ての数値より前に並べる
+    First,
+    /// 全ての数値より後に並べる
+    Last,
+    /// NaN を含む場合はソートせずにエラーを返す
+    Error,
+}
+
+impl NanPolicy {
+    /// この方針に従って `a` と `b` を比較する。
+    ///
+    /// 数値同士は IEEE 754 の全順序 ([`f64::total_cmp`]) で比較するので、`-0.0` は常に `0.0` より前になる。
+    /// NaN 同士も符号とペイロードのビット列で順序が決まるため、不安定なソートでも結果は一意になる。
+    /// [`NanPolicy::Error`] では NaN を [`NanPolicy::Last`] と同じ位置に並べる。
+    pub fn compare(self, a: &f64, b: &f64) -> Ordering {
+        match (a.is_nan(), b.is_nan()) {
+            (false, false) | (true, true) => a.total_cmp(b),
+            (true, false) if self == Self::First => Ordering::Less,
+            (true, false) => Ordering::Greater,
+            (false, true) => self.compare(b, a).reverse(),
+        }
+    }
+}
+
+/// ソート対象に NaN が含まれていた
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NanError {
+    /// 最初の NaN の位置
+    pub index: usize,
+}
+
+impl fmt::Display for NanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NaN found at index {}", self.index)
+    }
+}
+
+impl Error for NanError {}
+
+/// `policy` に従って `f64` のスライスを昇順にソートする。ソートには [`tim_sort::tim_by`] を使う。
+///
+/// # Errors
+/// `policy` が [`NanPolicy::Error`] で `arr` に NaN が含まれる場合は、`arr` を変更せずにエラーを返す。
+pub fn sort_floats(arr: &mut [f64], policy: NanPolicy) -> Result<(), NanError> {
+    sort_floats_with(arr, policy, |arr, compare| tim_sort::tim_by(arr, compare))
+}
+
+/// [`sort_floats`] を実行し、比較・交換回数を返す。
+///
+/// # Errors
+/// [`sort_floats`] と同じ。
+pub fn sort_floats_counted(
+    arr: &mut [f64],
+    policy: NanPolicy,
+) -> Result<VerifySort<'_, f64>, NanError> {
+    check_nan(arr, policy)?;
+    Ok(super::count(arr, |arr, rec| {
+        tim_sort::tim_impl(arr, &mut |a, b| policy.compare(a, b).is_lt(), rec)
+    }))
+}
+
+/// `policy` に従う比較関数を `sort_by` に渡し、`f64` のスライスをソートする。
+///
+/// `sort_by` には `|arr, compare| heap_by(arr, compare)` のように、比較関数を取るソートを渡す。
+///
+/// # Errors
+/// `policy` が [`NanPolicy::Error`] で `arr` に NaN が含まれる場合は、`arr` を変更せずにエラーを返す。
+pub fn sort_floats_with<S>(arr: &mut [f64], policy: NanPolicy, sort_by: S) -> Result<(), NanError>
+where
+    S: FnOnce(&mut [f64], &mut dyn FnMut(&f64, &f64) -> Ordering),
+{
+    check_nan(arr, policy)?;
+    sort_by(arr, &mut |a, b| policy.compare(a, b));
+    Ok(())
+}
+
+/// `policy` が [`NanPolicy::Error`] なら、`arr` に NaN がないことを確かめる。
+fn check_nan(arr: &[f64], policy: NanPolicy) -> Result<(), NanError> {
+    if policy == NanPolicy::Error
+        && let Some(index) = arr.iter().position(|x| x.is_nan())
+    {
+        return Err(NanError { index });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sort::{exchange_sort, insertion_sort, merge_sort, selection_sort};
+
+    /// 欠損値（NaN）と符号付きゼロを含む測定値
+    fn readings() -> Vec<f64> {
+        vec![
+            3.5,
+            f64::NAN,
+            -0.0,
+            1.25,
+            0.0,
+            -f64::NAN,
+            f64::NEG_INFINITY,
+            -0.0,
+            f64::INFINITY,
+            -2.0,
+        ]
+    }
+
+    fn bits(v: &[f64]) -> Vec<u64> {
+        v.iter().map(|x| x.to_bits()).collect()
+    }
+
+    #[test]
+    fn test_nan_last() {
+        let mut v = readings();
+        sort_floats(&mut v, NanPolicy::Last).unwrap();
+        let numbers = [
+            f64::NEG_INFINITY,
+            -2.0,
+            -0.0,
+            -0.0,
+            0.0,
+            1.25,
+            3.5,
+            f64::INFINITY,
+        ];
+        assert_eq!(bits(&v[..8]), bits(&numbers));
+        assert!(v[8..].iter().all(|x| x.is_nan()));
+        // 負の NaN は正の NaN より前
+        assert!(v[8].is_sign_negative() && v[9].is_sign_positive());
+    }
+
+    #[test]
+    fn test_nan_first() {
+        let mut v = readings();
+        sort_floats(&mut v, NanPolicy::First).unwrap();
+        assert!(v[..2].iter().all(|x| x.is_nan()));
+        assert_eq!(v[2], f64::NEG_INFINITY);
+        assert_eq!(v[9], f64::INFINITY);
+    }
+
+    #[test]
+    fn test_nan_error() {
+        let mut v = readings();
+        assert_eq!(
+            sort_floats(&mut v, NanPolicy::Error),
+            Err(NanError { index: 1 })
+        );
+        assert_eq!(bits(&v), bits(&readings()));
+
+        let mut v = vec![1.0, -0.0, 0.0, -1.0];
+        sort_floats(&mut v, NanPolicy::Error).unwrap();
+        assert_eq!(bits(&v), bits(&[-1.0, -0.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn test_all_sorts_agree() {
+        type SortBy = fn(&mut [f64], &mut dyn FnMut(&f64, &f64) -> Ordering);
+        let sorts: [SortBy; 6] = [
+            |arr, compare| exchange_sort::quick_by(arr, compare),
+            |arr, compare| exchange_sort::intro_by(arr, compare),
+            |arr, compare| selection_sort::selection_by(arr, compare),
+            |arr, compare| selection_sort::heap_by(arr, compare),
+            |arr, compare| insertion_sort::insertion_by(arr, compare),
+            |arr, compare| merge_sort::merge_by(arr, compare),
+        ];
+
+        for policy in [NanPolicy::First, NanPolicy::Last] {
+            let mut expected = readings();
+            sort_floats(&mut expected, policy).unwrap();
+            for sort_by in sorts {
+                let mut v = readings();
+                sort_floats_with(&mut v, policy, sort_by).unwrap();
+                assert_eq!(bits(&v), bits(&expected), "{policy:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use super::Complexity;
+
+/// シェルソートとコムソートで使う間隔の列
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GapSequence {
+    /// n/2, n/4, ..., 1（Shell, 1959）
+    Shell,
+    /// 1, 4, 13, 40, ... = (3^k - 1) / 2（Knuth, 1973）
+    Knuth,
+    /// 1, 5, 19, 41, 109, ... = 9・4^k - 9・2^k + 1 と 4^k - 3・2^k + 1 を交互に（Sedgewick, 1986）
+    Sedgewick,
+    /// 1, 4, 10, 23, 57, 132, 301, 701 の後は 2.25 倍ずつ（Ciura, 2001）
+    Ciura,
+    /// 1, 4, 9, 20, 46, 103, ... = ceil((9 (9/4)^k - 4) / 5)（Tokuda, 1992）
+    Tokuda,
+}
+
+/// Ciura が実験で求めた間隔
+const CIURA: [usize; 8] = [1, 4, 10, 23, 57, 132, 301, 701];
+
+impl GapSequence {
+    /// 全ての間隔の列
+    pub const ALL: [GapSequence; 5] = [
+        Self::Shell,
+        Self::Knuth,
+        Self::Sedgewick,
+        Self::Ciura,
+        Self::Tokuda,
+    ];
+
+    /// 長さ `len` の入力に使う間隔を、大きい順に返す。
+    ///
+    /// 間隔は全て `len` 未満で、最後は必ず 1 になる。`len` が 1 以下なら空。
+    pub fn gaps(self, len: usize) -> Vec<usize> {
+        if len <= 1 {
+            return Vec::new();
+        }
+
+        let mut gaps = match self {
+            Self::Shell => {
+                let mut gaps = Vec::new();
+                let mut gap = len / 2;
+                while gap > 0 {
+                    gaps.push(gap);
+                    gap /= 2;
+                }
+                gaps.reverse();
+                gaps
+            }
+            // Knuth の推奨に従い、len / 3 を超える間隔は使わない
+            Self::Knuth => increasing(|gap| 3 * gap + 1, 1, len.div_ceil(3).max(2)),
+            Self::Sedgewick => (0..)
+                .map(|k: u32| {
+                    if k.is_multiple_of(2) {
+                        9 * (2usize.pow(k) - 2usize.pow(k / 2)) + 1
+                    } else {
+                        8 * 2usize.pow(k) - 6 * 2usize.pow(k.div_ceil(2)) + 1
+                    }
+                })
+                .take_while(|&gap| gap < len)
+                .collect(),
+            Self::Ciura => {
+                let mut gaps = CIURA
+                    .into_iter()
+                    .take_while(|&gap| gap < len)
+                    .collect::<Vec<_>>();
+                if gaps.len() == CIURA.len() {
+                    let last = *gaps.last().unwrap();
+                    gaps.extend(increasing(|gap| gap * 9 / 4, last, len).into_iter().skip(1));
+                }
+                gaps
+            }
+            Self::Tokuda => (0..)
+                .map(|k| ((9.0 * 2.25f64.powi(k) - 4.0) / 5.0).ceil() as usize)
+                .take_while(|&gap| gap < len)
+                .collect(),
+        };
+        gaps.reverse();
+        gaps
+    }
+
+    /// シェルソートに使ったときの最悪の計算量
+    pub fn worst(self) -> Complexity {
+        match self {
+            Self::Shell => Complexity::Quadratic,
+            Self::Knuth => Complexity::ThreeHalves,
+            Self::Sedgewick => Complexity::FourThirds,
+            Self::Ciura | Self::Tokuda => Complexity::Unknown,
+        }
+    }
+}
+
+/// `first` から `next` を繰り返し適用した、`limit` 未満の値の昇順の列
+fn increasing(next: impl Fn(usize) -> usize, first: usize, limit: usize) -> Vec<usize> {
+    let mut values = Vec::new();
+    let mut value = first;
+    while value < limit {
+        values.push(value);
+        value = next(value);
+    }
+    values
+}
+
+impl Display for GapSequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Shell => "shell",
+            Self::Knuth => "knuth",
+            Self::Sedgewick => "sedgewick",
+            Self::Ciura => "ciura",
+            Self::Tokuda => "tokuda",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for GapSequence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|gaps| gaps.to_string() == s)
+            .ok_or_else(|| format!("unknown gap sequence: {s}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gaps() {
+        assert_eq!(GapSequence::Shell.gaps(20), [10, 5, 2, 1]);
+        assert_eq!(GapSequence::Knuth.gaps(200), [40, 13, 4, 1]);
+        assert_eq!(
+            GapSequence::Sedgewick.gaps(1000),
+            [929, 505, 209, 109, 41, 19, 5, 1]
+        );
+        assert_eq!(GapSequence::Ciura.gaps(100), [57, 23, 10, 4, 1]);
+        assert_eq!(GapSequence::Ciura.gaps(5000)[..3], [3548, 1577, 701]);
+        assert_eq!(
+            GapSequence::Tokuda.gaps(600),
+            [525, 233, 103, 46, 20, 9, 4, 1]
+        );
+    }
+
+    #[test]
+    fn test_gaps_end_with_one() {
+        for sequence in GapSequence::ALL {
+            assert!(sequence.gaps(0).is_empty());
+            assert!(sequence.gaps(1).is_empty());
+            for len in [2, 3, 10, 1000] {
+                let gaps = sequence.gaps(len);
+                assert_eq!(gaps.last(), Some(&1), "{sequence} {len}");
+                assert!(gaps.windows(2).all(|w| w[0] > w[1]), "{sequence} {gaps:?}");
+                assert!(gaps[0] < len);
+            }
+            assert_eq!(sequence.to_string().parse(), Ok(sequence));
+        }
+        assert!("pratt".parse::<GapSequence>().is_err());
+    }
+}
@@ -0,0 +1,338 @@
+use std::f64::consts::TAU;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use rand::rngs::StdRng;
+use rand::seq::{SliceRandom, index};
+use rand::{Rng, SeedableRng};
+
+/// 入力データの分布
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Distribution {
+    /// 重複のないランダムな並び（[`super::make_random_vector`] と同じく `0..len*10` から選ぶ）
+    Random,
+    /// 昇順にソート済み
+    Sorted,
+    /// 降順にソート済み
+    Reversed,
+    /// ソート済みの列に対して、ランダムな2要素の交換を `swaps` 回行ったもの
+    NearlySorted { swaps: usize },
+    /// `0..unique` の値だけからなるランダムな並び
+    FewUnique { unique: usize },
+    /// 昇順の区間が `teeth` 個続くのこぎり型
+    Sawtooth { teeth: usize },
+    /// 中央まで昇順、そこから降順になる山型
+    OrganPipe,
+    /// 平均 0、標準偏差 `len` の正規分布
+    Gaussian,
+}
+
+impl Distribution {
+    /// パラメータに既定値を使った全ての分布
+    pub const ALL: [Distribution; 8] = [
+        Self::Random,
+        Self::Sorted,
+        Self::Reversed,
+        Self::NearlySorted { swaps: 10 },
+        Self::FewUnique { unique: 10 },
+        Self::Sawtooth { teeth: 8 },
+        Self::OrganPipe,
+        Self::Gaussian,
+    ];
+}
+
+/// `nearly-sorted:10` のように、パラメータは `:` の後に書く。
+impl Display for Distribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Random => write!(f, "random"),
+            Self::Sorted => write!(f, "sorted"),
+            Self::Reversed => write!(f, "reversed"),
+            Self::NearlySorted { swaps } => write!(f, "nearly-sorted:{swaps}"),
+            Self::FewUnique { unique } => write!(f, "few-unique:{unique}"),
+            Self::Sawtooth { teeth } => write!(f, "sawtooth:{teeth}"),
+            Self::OrganPipe => write!(f, "organ-pipe"),
+            Self::Gaussian => write!(f, "gaussian"),
+        }
+    }
+}
+
+/// パラメータを省略した場合は [`Distribution::ALL`] の既定値を使う。
+impl FromStr for Distribution {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, param) = match s.split_once(':') {
+            Some((name, param)) => {
+                let param = param
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid parameter for {name}: {param}"))?;
+                (name, Some(param))
+            }
+            None => (s, None),
+        };
+
+        let distribution = Self::ALL
+            .into_iter()
+            .find(|d| d.to_string().split(':').next() == Some(name))
+            .ok_or_else(|| format!("unknown distribution: {name}"))?;
+
+        match (distribution, param) {
+            (d, None) => Ok(d),
+            (Self::NearlySorted { .. }, Some(swaps)) => Ok(Self::NearlySorted { swaps }),
+            (Self::FewUnique { .. }, Some(unique)) if unique > 0 => Ok(Self::FewUnique { unique }),
+            (Self::Sawtooth { .. }, Some(teeth)) if teeth > 0 => Ok(Self::Sawtooth { teeth }),
+            (d, Some(param)) => Err(format!("invalid parameter for {d}: {param}")),
+        }
+    }
+}
+
+/// 生成する要素の型。分布に従って作った数値（キー）から要素を作る。
+///
+/// キーの大小関係は要素の大小関係として保たれる。
+pub trait Element {
+    fn from_key(key: f64) -> Self;
+}
+
+/// キーを四捨五入する。範囲外の値は `i32` の最小値・最大値になる。
+impl Element for i32 {
+    fn from_key(key: f64) -> Self {
+        key.round() as i32
+    }
+}
+
+impl Element for f64 {
+    fn from_key(key: f64) -> Self {
+        key
+    }
+}
+
+/// 文字列の要素の接頭辞。昇順に並び、どれも他の接頭辞にはならない。
+///
+/// 前半の7つは負のキー、後半の7つは0以上のキーに、キーの桁数ごとに使う。
+const STRING_PREFIXES: [&str; 14] = [
+    "/home/alice/",
+    "/home/bob/projects/",
+    "/opt/",
+    "/srv/www/static/",
+    "/tmp/",
+    "/usr/include/",
+    "/usr/lib/",
+    "/usr/lib64/",
+    "/usr/local/bin/",
+    "/usr/local/lib/",
+    "/usr/share/doc/",
+    "/usr/share/man/",
+    "/var/cache/",
+    "/var/log/",
+];
+
+/// `i32` に変換したキーを、順序を保つようにパスのような可変長の文字列で表す。
+///
+/// キーの絶対値を26進数の英小文字で書き、符号と桁数で決まる接頭辞を付ける。
+/// 桁数が同じなら英小文字の部分の辞書順が数値の順になり、桁数が違えば接頭辞の順で決まる。
+/// 負のキーは、桁数の多いものほど前の接頭辞を使い、桁を反転して大小を逆にする。
+impl Element for String {
+    fn from_key(key: f64) -> Self {
+        let key = i32::from_key(key);
+        // 負のキーは -1 を 0 に、i32::MIN を i32::MAX に移す。26^7 > 2^31 なので、桁数は7以下
+        let magnitude = if key < 0 { !key } else { key } as u32;
+        let digits = magnitude.checked_ilog(26).map_or(1, |d| d as usize + 1);
+
+        let mut bytes = vec![b'a'; digits];
+        let mut n = magnitude;
+        for byte in bytes.iter_mut().rev() {
+            *byte = b'a' + (n % 26) as u8;
+            n /= 26;
+        }
+
+        let prefix = if key < 0 {
+            for byte in &mut bytes {
+                *byte = b'z' - (*byte - b'a');
+            }
+            STRING_PREFIXES[7 - digits]
+        } else {
+            STRING_PREFIXES[6 + digits]
+        };
+        format!("{prefix}{}", String::from_utf8(bytes).unwrap())
+    }
+}
+
+/// シードを指定して、再現可能な入力データを作る。
+///
+/// 同じシード・同じ順序で呼び出せば、常に同じ入力が得られる。
+#[derive(Debug, Clone)]
+pub struct Generator {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl Generator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// 作成時に指定したシード
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// 分布 `distribution` に従う長さ `len` の入力を作る。
+    pub fn generate<T: Element>(&mut self, distribution: Distribution, len: usize) -> Vec<T> {
+        self.keys(distribution, len)
+            .into_iter()
+            .map(T::from_key)
+            .collect()
+    }
+
+    fn keys(&mut self, distribution: Distribution, len: usize) -> Vec<f64> {
+        let rng = &mut self.rng;
+        match distribution {
+            Distribution::Random => random_keys(rng, len),
+            Distribution::Sorted => {
+                let mut keys = random_keys(rng, len);
+                keys.sort_by(f64::total_cmp);
+                keys
+            }
+            Distribution::Reversed => {
+                let mut keys = random_keys(rng, len);
+                keys.sort_by(|a, b| b.total_cmp(a));
+                keys
+            }
+            Distribution::NearlySorted { swaps } => {
+                let mut keys = random_keys(rng, len);
+                keys.sort_by(f64::total_cmp);
+                if len > 1 {
+                    for _ in 0..swaps {
+                        keys.swap(rng.random_range(0..len), rng.random_range(0..len));
+                    }
+                }
+                keys
+            }
+            Distribution::FewUnique { unique } => (0..len)
+                .map(|_| rng.random_range(0..unique.max(1)) as f64)
+                .collect(),
+            Distribution::Sawtooth { teeth } => {
+                let period = len.div_ceil(teeth.max(1)).max(1);
+                (0..len).map(|i| (i % period) as f64).collect()
+            }
+            Distribution::OrganPipe => (0..len).map(|i| i.min(len - 1 - i) as f64).collect(),
+            Distribution::Gaussian => {
+                let stddev = len as f64;
+                (0..len).map(|_| gaussian(rng) * stddev).collect()
+            }
+        }
+    }
+}
+
+/// `0..len*10` から重複なく選んだ `len` 個の値を、ランダムな順に並べる。
+fn random_keys(rng: &mut StdRng, len: usize) -> Vec<f64> {
+    let mut keys = index::sample(rng, len * 10, len)
+        .into_iter()
+        .map(|i| i as f64)
+        .collect::<Vec<_>>();
+    keys.shuffle(rng);
+    keys
+}
+
+/// 標準正規分布に従う値をボックス＝ミュラー法で作る。
+fn gaussian(rng: &mut StdRng) -> f64 {
+    // ln(0) を避けるため、u1 は (0, 1] から選ぶ
+    let u1 = 1.0 - rng.random::<f64>();
+    let u2 = rng.random::<f64>();
+    (-2.0 * u1.ln()).sqrt() * (TAU * u2).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reproducible() {
+        for distribution in Distribution::ALL {
+            let a = Generator::new(42).generate::<i32>(distribution, 1000);
+            let b = Generator::new(42).generate::<i32>(distribution, 1000);
+            assert_eq!(a, b, "{distribution}");
+        }
+
+        let a = Generator::new(1).generate::<i32>(Distribution::Random, 1000);
+        let b = Generator::new(2).generate::<i32>(Distribution::Random, 1000);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_shapes() {
+        let mut generator = Generator::new(0);
+        let n = 1000;
+
+        let v = generator.generate::<i32>(Distribution::Random, n);
+        let mut distinct = v.clone();
+        distinct.sort();
+        distinct.dedup();
+        assert_eq!(distinct.len(), n);
+
+        let v = generator.generate::<i32>(Distribution::Sorted, n);
+        assert!(v.windows(2).all(|w| w[0] <= w[1]));
+        let v = generator.generate::<i32>(Distribution::Reversed, n);
+        assert!(v.windows(2).all(|w| w[0] >= w[1]));
+
+        // 交換1回につき、隣接する順序の逆転は高々4か所増える
+        let v = generator.generate::<i32>(Distribution::NearlySorted { swaps: 5 }, n);
+        assert!(v.windows(2).filter(|w| w[0] > w[1]).count() <= 20);
+
+        let v = generator.generate::<i32>(Distribution::FewUnique { unique: 3 }, n);
+        assert!(v.iter().all(|x| (0..3).contains(x)));
+
+        let v = generator.generate::<i32>(Distribution::Sawtooth { teeth: 4 }, n);
+        assert_eq!(v.windows(2).filter(|w| w[0] > w[1]).count(), 3);
+
+        let v = generator.generate::<i32>(Distribution::OrganPipe, 9);
+        assert_eq!(v, [0, 1, 2, 3, 4, 3, 2, 1, 0]);
+
+        let v = generator.generate::<f64>(Distribution::Gaussian, 10_000);
+        let mean = v.iter().sum::<f64>() / v.len() as f64;
+        let stddev = (v.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / v.len() as f64).sqrt();
+        assert!(mean.abs() < 500.0, "{mean}");
+        assert!((stddev / 10_000.0 - 1.0).abs() < 0.05, "{stddev}");
+    }
+
+    #[test]
+    fn test_string_keeps_order() {
+        let mut v = Generator::new(7).generate::<f64>(Distribution::Gaussian, 1000);
+        v.sort_by(f64::total_cmp);
+        let strings = v.into_iter().map(String::from_key).collect::<Vec<_>>();
+        assert!(strings.windows(2).all(|w| w[0] <= w[1]));
+
+        let keys = [i32::MIN, -26, -25, -1, 0, 1, 25, 26, 675, 676, i32::MAX];
+        let strings = keys.map(|k| String::from_key(k as f64));
+        assert!(strings.windows(2).all(|w| w[0] < w[1]), "{strings:?}");
+        assert_eq!(strings[4], "/usr/lib64/a");
+        assert_eq!(strings[8], "/usr/local/bin/zz");
+        assert_eq!(strings[3], "/usr/lib/z");
+
+        // 接頭辞は昇順で、互いに他の接頭辞にならない
+        assert!(STRING_PREFIXES.windows(2).all(|w| w[0] < w[1]));
+        assert!(STRING_PREFIXES.windows(2).all(|w| !w[1].starts_with(w[0])));
+    }
+
+    #[test]
+    fn test_parse_distribution() {
+        for distribution in Distribution::ALL {
+            assert_eq!(distribution.to_string().parse(), Ok(distribution));
+        }
+        assert_eq!(
+            "nearly-sorted".parse(),
+            Ok(Distribution::NearlySorted { swaps: 10 })
+        );
+        assert_eq!(
+            "few-unique:2".parse(),
+            Ok(Distribution::FewUnique { unique: 2 })
+        );
+        assert!("organ-pipe:3".parse::<Distribution>().is_err());
+        assert!("sawtooth:0".parse::<Distribution>().is_err());
+        assert!("zigzag".parse::<Distribution>().is_err());
+    }
+}
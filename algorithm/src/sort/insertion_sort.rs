@@ -0,0 +1,146 @@
+use std::cmp::Ordering;
+
+use super::gap::GapSequence;
+use super::trace::{self, Trace};
+use super::{Recorder, VerifySort};
+
+/// 挿入ソート (平均: O(n^2), 最悪: O(n^2))
+/// 0番目から基準までの要素がソート済みになる。基準を1番目から順番にしていく。
+///
+/// 1. 1番目の要素を基準にして、0番目の要素と比較し、順序が逆なら交換する。
+/// 2. 2番目の要素を基準にする。
+///     1. 基準と1番目の要素と比較し、順序が逆なら1番目の要素を2番目に代入する。順序が正しいなら基準の要素を2番目に代入（実質そのまま）し、2番目基準終了。
+///     2. 基準と0番目の要素と比較し、順序が逆なら0番目の要素を1番目に代入する。順序が正しいなら基準の要素を1番目に代入し、2番目基準終了。
+///     3. 基準の要素を0番目に代入し、2番目基準終了。
+/// 3. 3番目の要素を基準にする。
+/// 4. これを繰り返して、全ての要素がソートされるまで続ける。
+pub fn insertion<T: PartialOrd>(src: &mut [T]) {
+    insertion_impl(src, &mut T::lt, &mut ());
+}
+
+/// 比較関数 `compare` を用いて [`insertion`] を行う。
+pub fn insertion_by<T, F>(src: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    insertion_impl(src, &mut |a, b| compare(a, b) == Ordering::Less, &mut ());
+}
+
+/// キー抽出関数 `f` が返すキーの順に [`insertion`] を行う。
+pub fn insertion_by_key<T, K, F>(src: &mut [T], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    insertion_impl(src, &mut |a, b| f(a).lt(&f(b)), &mut ());
+}
+
+/// [`insertion`] を実行し、比較・代入回数を返す。
+pub fn insertion_counted<T: PartialOrd>(src: &mut [T]) -> VerifySort<'_, T> {
+    super::count(src, |src, rec| insertion_impl(src, &mut T::lt, rec))
+}
+
+/// [`insertion`] を実行し、比較・代入の記録を返す。
+pub fn insertion_traced<T: PartialOrd + Clone>(src: &mut [T]) -> Trace<T> {
+    trace::trace(src, |src, rec| insertion_impl(src, &mut T::lt, rec))
+}
+
+pub(super) fn insertion_impl<T, F, R>(src: &mut [T], is_less: &mut F, rec: &mut R)
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    let n = src.len();
+
+    for i in 1..n {
+        // 基準 src[i] より大きい要素を飛ばして、基準の挿入位置 j を探す
+        let mut j = i;
+        while j > 0 {
+            rec.compare(&src[i], &src[j - 1]);
+            if !is_less(&src[i], &src[j - 1]) {
+                break;
+            }
+            j -= 1;
+        }
+
+        // 基準が既に正しい位置にあれば動かさない
+        if j == i {
+            continue;
+        }
+
+        // src[j..i] を1つ右にずらし、基準を j 番目に代入する
+        src[j..=i].rotate_right(1);
+        for elem in &src[j..=i] {
+            rec.write(elem);
+        }
+    }
+}
+
+/// シェルソート (平均: 間隔列による, 最悪: [`GapSequence::worst`])
+/// 離れた要素同士を先に挿入ソートしておき、最後の通常の挿入ソートで動かす距離を短くする。
+///
+/// 1. 間隔列 `gaps` の最も大きい間隔 h を選び、h 個おきの要素からなる列をそれぞれ挿入ソートする（h-ソート）。
+/// 2. 次に大きい間隔で同様に h-ソートする。
+/// 3. 最後に間隔 1 で h-ソート（通常の挿入ソート）すると、全体がソート済みになる。
+///
+/// h-ソートでは基準を h 個前の要素と比較し、順序が逆なら交換して、さらに h 個前と比較していく。
+pub fn shell<T: PartialOrd>(src: &mut [T], gaps: GapSequence) {
+    shell_impl(src, gaps, &mut T::lt, &mut ());
+}
+
+/// 比較関数 `compare` を用いて [`shell`] を行う。
+pub fn shell_by<T, F>(src: &mut [T], gaps: GapSequence, mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    shell_impl(
+        src,
+        gaps,
+        &mut |a, b| compare(a, b) == Ordering::Less,
+        &mut (),
+    );
+}
+
+/// キー抽出関数 `f` が返すキーの順に [`shell`] を行う。
+pub fn shell_by_key<T, K, F>(src: &mut [T], gaps: GapSequence, mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    shell_impl(src, gaps, &mut |a, b| f(a).lt(&f(b)), &mut ());
+}
+
+/// [`shell`] を実行し、比較・交換回数を返す。
+pub fn shell_counted<T: PartialOrd>(src: &mut [T], gaps: GapSequence) -> VerifySort<'_, T> {
+    super::count(src, |src, rec| shell_impl(src, gaps, &mut T::lt, rec))
+}
+
+fn shell_impl<T, F, R>(src: &mut [T], gaps: GapSequence, is_less: &mut F, rec: &mut R)
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    for gap in gaps.gaps(src.len()) {
+        h_insertion(src, gap, is_less, rec);
+    }
+}
+
+/// `gap` 個おきの要素からなる列をそれぞれ挿入ソートする。
+fn h_insertion<T, F, R>(src: &mut [T], gap: usize, is_less: &mut F, rec: &mut R)
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    for i in gap..src.len() {
+        let mut j = i;
+        while j >= gap {
+            rec.compare(&src[j], &src[j - gap]);
+            if !is_less(&src[j], &src[j - gap]) {
+                break;
+            }
+            src.swap(j - gap, j);
+            rec.swap(&src[j - gap], &src[j]);
+            j -= gap;
+        }
+    }
+}
@@ -0,0 +1,300 @@
+use std::cmp::Ordering;
+use std::mem;
+
+use super::trace::{self, Trace};
+use super::{Recorder, VerifySort};
+
+/// マージソート (平均: O(n log n), 最悪: O(n log n))
+/// 分割統治法を用いて、配列を再帰的に分割し、ソートされた部分配列をマージする。
+///
+/// 作業領域として `src` と同じ長さのバッファを最初に1つだけ確保し、
+/// 再帰の段ごとに `src` とバッファの役割を入れ替えながらマージする（ピンポン方式）。
+/// 要素は交換（`mem::swap`）で移動するため、バッファの確保時以外に `clone` は行わない。
+pub fn merge<T: PartialOrd + Clone>(src: &mut [T]) {
+    merge_impl(src, &mut T::lt, &mut ());
+}
+
+/// 比較関数 `compare` を用いて [`merge`] を行う。
+pub fn merge_by<T, F>(src: &mut [T], mut compare: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    merge_impl(src, &mut |a, b| compare(a, b) == Ordering::Less, &mut ());
+}
+
+/// キー抽出関数 `f` が返すキーの順に [`merge`] を行う。
+pub fn merge_by_key<T, K, F>(src: &mut [T], mut f: F)
+where
+    T: Clone,
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    merge_impl(src, &mut |a, b| f(a).lt(&f(b)), &mut ());
+}
+
+/// [`merge`] を実行し、比較・代入回数を返す。
+pub fn merge_counted<T: PartialOrd + Clone>(src: &mut [T]) -> VerifySort<'_, T> {
+    super::count(src, |src, rec| merge_impl(src, &mut T::lt, rec))
+}
+
+/// [`merge`] を実行し、比較・代入の記録を返す。
+pub fn merge_traced<T: PartialOrd + Clone>(src: &mut [T]) -> Trace<T> {
+    trace::trace(src, |src, rec| merge_impl(src, &mut T::lt, rec))
+}
+
+fn merge_impl<T, F, R>(src: &mut [T], is_less: &mut F, rec: &mut R)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    if src.len() <= 1 {
+        return;
+    }
+
+    let mut buf = src.to_vec();
+    split_merge(src, &mut buf, false, is_less, rec);
+}
+
+/// `src` の要素をソートし、`into_buf` が `true` なら `buf` に、`false` なら `src` に格納する。
+///
+/// 左右の半分をそれぞれ格納先と反対側にソートしてから、格納先へマージする。
+pub(super) fn split_merge<T, F, R>(
+    src: &mut [T],
+    buf: &mut [T],
+    into_buf: bool,
+    is_less: &mut F,
+    rec: &mut R,
+) where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    let n = src.len();
+    if n == 1 {
+        if into_buf {
+            mem::swap(&mut src[0], &mut buf[0]);
+            rec.swap(&buf[0], &src[0]);
+        }
+        return;
+    }
+
+    let mid = n / 2;
+    {
+        let (src_left, src_right) = src.split_at_mut(mid);
+        let (buf_left, buf_right) = buf.split_at_mut(mid);
+        split_merge(src_left, buf_left, !into_buf, is_less, rec);
+        split_merge(src_right, buf_right, !into_buf, is_less, rec);
+    }
+
+    if into_buf {
+        merge_into(src, mid, buf, is_less, rec);
+    } else {
+        merge_into(buf, mid, src, is_less, rec);
+    }
+}
+
+/// ボトムアップ型マージソート (平均: O(n log n), 最悪: O(n log n))
+/// 再帰を使わず、長さ 1, 2, 4, ... の隣接する区間を順にマージしていく。
+///
+/// 1. 長さ1の区間を2つずつマージし、長さ2のソート済み区間を作る。
+/// 2. 長さ2の区間を2つずつマージし、長さ4のソート済み区間を作る。
+/// 3. 区間の長さが n 以上になるまで繰り返す。
+///
+/// 各段のマージは `src` とバッファの間で交互に行うため、スタックの深さは入力の大きさによらない。
+pub fn merge_bottom_up<T: PartialOrd + Clone>(src: &mut [T]) {
+    merge_bottom_up_impl(src, &mut T::lt, &mut ());
+}
+
+/// 比較関数 `compare` を用いて [`merge_bottom_up`] を行う。
+pub fn merge_bottom_up_by<T, F>(src: &mut [T], mut compare: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    merge_bottom_up_impl(src, &mut |a, b| compare(a, b) == Ordering::Less, &mut ());
+}
+
+/// キー抽出関数 `f` が返すキーの順に [`merge_bottom_up`] を行う。
+pub fn merge_bottom_up_by_key<T, K, F>(src: &mut [T], mut f: F)
+where
+    T: Clone,
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    merge_bottom_up_impl(src, &mut |a, b| f(a).lt(&f(b)), &mut ());
+}
+
+/// [`merge_bottom_up`] を実行し、比較・代入回数を返す。
+pub fn merge_bottom_up_counted<T: PartialOrd + Clone>(src: &mut [T]) -> VerifySort<'_, T> {
+    super::count(src, |src, rec| merge_bottom_up_impl(src, &mut T::lt, rec))
+}
+
+fn merge_bottom_up_impl<T, F, R>(src: &mut [T], is_less: &mut F, rec: &mut R)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    let n = src.len();
+    if n <= 1 {
+        return;
+    }
+
+    let mut buf = src.to_vec();
+    // true の間はソート途中の要素が src に、false の間は buf にある
+    let mut in_src = true;
+    let mut width = 1;
+
+    while width < n {
+        let (from, to) = if in_src {
+            (&mut *src, &mut buf[..])
+        } else {
+            (&mut buf[..], &mut *src)
+        };
+
+        for start in (0..n).step_by(2 * width) {
+            let mid = (start + width).min(n);
+            let end = (start + 2 * width).min(n);
+            merge_into(
+                &mut from[start..end],
+                mid - start,
+                &mut to[start..end],
+                is_less,
+                rec,
+            );
+        }
+
+        in_src = !in_src;
+        width *= 2;
+    }
+
+    // 最後のマージ先が buf だった場合は src に戻す
+    if !in_src {
+        for (elem, sorted) in src.iter_mut().zip(buf.iter_mut()) {
+            mem::swap(elem, sorted);
+            rec.swap(elem, sorted);
+        }
+    }
+}
+
+/// `src` のソート済み区間 `src[..mid]` と `src[mid..]` を `dst` にマージする。
+///
+/// 要素は交換で移動するため、マージ後の `src` には `dst` に元々あった値が残る。
+/// 等しい要素は左側の区間を優先するので、安定ソートになる。
+pub(super) fn merge_into<T, F, R>(
+    src: &mut [T],
+    mid: usize,
+    dst: &mut [T],
+    is_less: &mut F,
+    rec: &mut R,
+) where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    let n = src.len();
+    let mut i = 0;
+    let mut j = mid;
+
+    for elem in dst[..n].iter_mut() {
+        // 右側が尽きたか、左側の先頭が右側の先頭以下なら左側から取る
+        let take_left = i < mid
+            && (j >= n || {
+                rec.compare(&src[j], &src[i]);
+                !is_less(&src[j], &src[i])
+            });
+
+        let from = if take_left {
+            i += 1;
+            i - 1
+        } else {
+            j += 1;
+            j - 1
+        };
+        mem::swap(elem, &mut src[from]);
+        rec.swap(elem, &src[from]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// key で比較し、id で元の順序を確認するための要素
+    #[derive(Debug, Clone)]
+    struct Item {
+        key: u32,
+        id: usize,
+    }
+
+    impl PartialEq for Item {
+        fn eq(&self, other: &Self) -> bool {
+            self.key == other.key
+        }
+    }
+
+    impl PartialOrd for Item {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            self.key.partial_cmp(&other.key)
+        }
+    }
+
+    fn make_items(n: usize) -> Vec<Item> {
+        (0..n)
+            .map(|id| Item {
+                key: (id * 7 % 5) as u32,
+                id,
+            })
+            .collect()
+    }
+
+    fn assert_stable(items: &[Item]) {
+        assert!(
+            items
+                .windows(2)
+                .all(|w| w[0].key < w[1].key || (w[0].key == w[1].key && w[0].id < w[1].id))
+        );
+    }
+
+    #[test]
+    fn test_merge_strings() {
+        let mut v = vec!["pear", "apple", "fig", "banana", "cherry"]
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        merge(&mut v);
+        assert_eq!(v, ["apple", "banana", "cherry", "fig", "pear"]);
+    }
+
+    #[test]
+    fn test_merge_stable() {
+        let mut v = make_items(101);
+        merge(&mut v);
+        assert_stable(&v);
+    }
+
+    #[test]
+    fn test_merge_bottom_up_stable() {
+        for n in [0, 1, 2, 3, 7, 8, 9, 100, 101] {
+            let mut v = make_items(n);
+            merge_bottom_up(&mut v);
+            assert_eq!(v.len(), n);
+            assert_stable(&v);
+        }
+    }
+
+    #[test]
+    fn test_merge_same_result() {
+        let v = crate::sort::make_random_vector(1000);
+        let mut expected = v.clone();
+        expected.sort();
+
+        let mut top_down = v.clone();
+        merge(&mut top_down);
+        assert_eq!(top_down, expected);
+
+        let mut bottom_up = v.clone();
+        merge_bottom_up(&mut bottom_up);
+        assert_eq!(bottom_up, expected);
+    }
+}
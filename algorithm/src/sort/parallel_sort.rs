@@ -0,0 +1,321 @@
+use std::cmp::Ordering;
+use std::num::NonZero;
+use std::thread;
+
+use super::{Recorder, VerifySort, exchange_sort, merge_sort};
+
+/// 並列ソートの設定。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParallelConfig {
+    /// 使用するスレッド数の上限（呼び出し元のスレッドを含む）
+    pub threads: usize,
+    /// この長さ以下の区間は新しいスレッドを作らず、逐次ソートする
+    pub cutoff: usize,
+}
+
+impl Default for ParallelConfig {
+    /// スレッド数は実行環境の並列度、区間の下限は 8192 要素とする。
+    fn default() -> Self {
+        Self {
+            threads: thread::available_parallelism().map_or(1, NonZero::get),
+            cutoff: 8192,
+        }
+    }
+}
+
+/// 並列クイックソート (平均: O(n log n), 最悪: O(n log n))
+///
+/// 1. [`exchange_sort::intro`] と同じく pivot を選び、三分割する。
+/// 2. 小さい要素群を新しいスレッドで、大きい要素群を今のスレッドでソートする。
+///     - スレッド数は左右に半分ずつ割り当てる。
+/// 3. スレッドを使い切るか区間が `cutoff` 以下になったら、[`exchange_sort::intro`] で逐次ソートする。
+pub fn quick<T>(arr: &mut [T], config: ParallelConfig)
+where
+    T: PartialOrd + Send,
+{
+    quick_impl(arr, config.threads, config.cutoff, &T::lt, &mut ());
+}
+
+/// 比較関数 `compare` を用いて [`quick`] を行う。
+pub fn quick_by<T, F>(arr: &mut [T], config: ParallelConfig, compare: F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    let is_less = |a: &T, b: &T| compare(a, b) == Ordering::Less;
+    quick_impl(arr, config.threads, config.cutoff, &is_less, &mut ());
+}
+
+/// キー抽出関数 `f` が返すキーの順に [`quick`] を行う。
+pub fn quick_by_key<T, K, F>(arr: &mut [T], config: ParallelConfig, f: F)
+where
+    T: Send,
+    F: Fn(&T) -> K + Sync,
+    K: Ord,
+{
+    let is_less = |a: &T, b: &T| f(a).lt(&f(b));
+    quick_impl(arr, config.threads, config.cutoff, &is_less, &mut ());
+}
+
+/// [`quick`] を実行し、全スレッドの比較・交換回数の合計を返す。
+pub fn quick_counted<T>(arr: &mut [T], config: ParallelConfig) -> VerifySort<'_, T>
+where
+    T: PartialOrd + Send,
+{
+    super::count(arr, |arr, rec| {
+        quick_impl(arr, config.threads, config.cutoff, &T::lt, rec)
+    })
+}
+
+fn quick_impl<T, F, R>(arr: &mut [T], threads: usize, cutoff: usize, is_less: &F, rec: &mut R)
+where
+    T: Send,
+    F: Fn(&T, &T) -> bool + Sync,
+    R: Recorder<T> + Default + Send,
+{
+    if threads <= 1 || arr.len() <= cutoff {
+        exchange_sort::intro_impl(arr, &mut &*is_less, rec);
+        return;
+    }
+
+    let pivot_index = exchange_sort::choose_pivot(arr, &mut &*is_less, rec);
+    let (lt, gt) = exchange_sort::partition3(arr, pivot_index, &mut &*is_less, rec);
+
+    let (left, rest) = arr.split_at_mut(lt);
+    let right = &mut rest[gt - lt..];
+    let left_threads = threads / 2;
+
+    let left_rec = thread::scope(|s| {
+        let handle = s.spawn(|| {
+            let mut left_rec = R::default();
+            quick_impl(left, left_threads, cutoff, is_less, &mut left_rec);
+            left_rec
+        });
+        quick_impl(right, threads - left_threads, cutoff, is_less, rec);
+        handle.join().unwrap()
+    });
+    rec.join(left_rec);
+}
+
+/// 並列マージソート (平均: O(n log n), 最悪: O(n log n))
+///
+/// 1. [`merge_sort::merge`] と同じく、作業領域のバッファを1つだけ確保する。
+/// 2. 左半分を新しいスレッドで、右半分を今のスレッドでソートし、両方の終了を待ってマージする。
+///     - スレッド数は左右に半分ずつ割り当てる。
+/// 3. スレッドを使い切るか区間が `cutoff` 以下になったら、逐次マージソートに切り替える。
+///
+/// 分割の仕方は逐次版と同じなので、比較・代入回数も逐次版と一致する。
+pub fn merge<T>(src: &mut [T], config: ParallelConfig)
+where
+    T: PartialOrd + Clone + Send,
+{
+    merge_impl(src, config, &T::lt, &mut ());
+}
+
+/// 比較関数 `compare` を用いて [`merge`] を行う。
+pub fn merge_by<T, F>(src: &mut [T], config: ParallelConfig, compare: F)
+where
+    T: Clone + Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    let is_less = |a: &T, b: &T| compare(a, b) == Ordering::Less;
+    merge_impl(src, config, &is_less, &mut ());
+}
+
+/// キー抽出関数 `f` が返すキーの順に [`merge`] を行う。
+pub fn merge_by_key<T, K, F>(src: &mut [T], config: ParallelConfig, f: F)
+where
+    T: Clone + Send,
+    F: Fn(&T) -> K + Sync,
+    K: Ord,
+{
+    let is_less = |a: &T, b: &T| f(a).lt(&f(b));
+    merge_impl(src, config, &is_less, &mut ());
+}
+
+/// [`merge`] を実行し、全スレッドの比較・代入回数の合計を返す。
+pub fn merge_counted<T>(src: &mut [T], config: ParallelConfig) -> VerifySort<'_, T>
+where
+    T: PartialOrd + Clone + Send,
+{
+    super::count(src, |src, rec| merge_impl(src, config, &T::lt, rec))
+}
+
+fn merge_impl<T, F, R>(src: &mut [T], config: ParallelConfig, is_less: &F, rec: &mut R)
+where
+    T: Clone + Send,
+    F: Fn(&T, &T) -> bool + Sync,
+    R: Recorder<T> + Default + Send,
+{
+    if src.len() <= 1 {
+        return;
+    }
+
+    let mut buf = src.to_vec();
+    split_merge(
+        src,
+        &mut buf,
+        false,
+        config.threads,
+        config.cutoff,
+        is_less,
+        rec,
+    );
+}
+
+/// [`merge_sort`] の `split_merge` の並列版。
+fn split_merge<T, F, R>(
+    src: &mut [T],
+    buf: &mut [T],
+    into_buf: bool,
+    threads: usize,
+    cutoff: usize,
+    is_less: &F,
+    rec: &mut R,
+) where
+    T: Send,
+    F: Fn(&T, &T) -> bool + Sync,
+    R: Recorder<T> + Default + Send,
+{
+    let n = src.len();
+    if threads <= 1 || n <= cutoff || n == 1 {
+        merge_sort::split_merge(src, buf, into_buf, &mut &*is_less, rec);
+        return;
+    }
+
+    let mid = n / 2;
+    let left_threads = threads / 2;
+    {
+        let (src_left, src_right) = src.split_at_mut(mid);
+        let (buf_left, buf_right) = buf.split_at_mut(mid);
+
+        let left_rec = thread::scope(|s| {
+            let handle = s.spawn(|| {
+                let mut left_rec = R::default();
+                split_merge(
+                    src_left,
+                    buf_left,
+                    !into_buf,
+                    left_threads,
+                    cutoff,
+                    is_less,
+                    &mut left_rec,
+                );
+                left_rec
+            });
+            split_merge(
+                src_right,
+                buf_right,
+                !into_buf,
+                threads - left_threads,
+                cutoff,
+                is_less,
+                rec,
+            );
+            handle.join().unwrap()
+        });
+        rec.join(left_rec);
+    }
+
+    if into_buf {
+        merge_sort::merge_into(src, mid, buf, &mut &*is_less, rec);
+    } else {
+        merge_sort::merge_into(buf, mid, src, &mut &*is_less, rec);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sort::make_random_vector;
+
+    fn configs() -> Vec<ParallelConfig> {
+        let mut configs = vec![ParallelConfig::default()];
+        for threads in [1, 2, 3, 4, 8] {
+            configs.push(ParallelConfig {
+                threads,
+                cutoff: 64,
+            });
+        }
+        configs
+    }
+
+    #[test]
+    fn test_parallel_quick() {
+        let v = make_random_vector(20_000);
+        let mut expected = v.clone();
+        expected.sort();
+
+        for config in configs() {
+            let mut w = v.clone();
+            quick(&mut w, config);
+            assert_eq!(w, expected, "{config:?}");
+        }
+    }
+
+    #[test]
+    fn test_parallel_merge() {
+        let v = make_random_vector(20_000);
+        let mut expected = v.clone();
+        expected.sort();
+
+        for config in configs() {
+            let mut w = v.clone();
+            merge(&mut w, config);
+            assert_eq!(w, expected, "{config:?}");
+        }
+    }
+
+    #[test]
+    fn test_parallel_by_key_stable() {
+        let v = make_random_vector(5_000)
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| (x % 10, i))
+            .collect::<Vec<_>>();
+        let config = ParallelConfig {
+            threads: 4,
+            cutoff: 16,
+        };
+
+        let mut merged = v.clone();
+        merge_by_key(&mut merged, config, |&(key, _)| key);
+        let mut expected = v.clone();
+        expected.sort_by_key(|&(key, _)| key);
+        assert_eq!(merged, expected);
+
+        let mut quicked = v.clone();
+        quick_by(&mut quicked, config, |a, b| b.cmp(a));
+        expected.sort_by(|a, b| b.cmp(a));
+        assert_eq!(quicked, expected);
+    }
+
+    #[test]
+    fn test_parallel_merge_counts_match_sequential() {
+        let v = make_random_vector(10_000);
+        let config = ParallelConfig {
+            threads: 4,
+            cutoff: 100,
+        };
+
+        let mut w = v.clone();
+        let sequential = merge_sort::merge_counted(&mut w);
+        let mut w = v.clone();
+        let parallel = merge_counted(&mut w, config);
+
+        assert_eq!(parallel.compare_count, sequential.compare_count);
+        assert_eq!(parallel.swap_count, sequential.swap_count);
+    }
+
+    #[test]
+    fn test_parallel_quick_counted() {
+        let mut v = make_random_vector(10_000);
+        let config = ParallelConfig {
+            threads: 4,
+            cutoff: 100,
+        };
+        let result = quick_counted(&mut v, config);
+        assert!(result.sorted.windows(2).all(|w| w[0] <= w[1]));
+        assert!(result.compare_count > 10_000);
+    }
+}
@@ -0,0 +1,522 @@
+use super::Complexity::{
+    self, Linear, LinearWithDigits, LinearWithRange, Linearithmic, LinearithmicSquared, Quadratic,
+    Unknown,
+};
+use super::float_sort::{self, NanPolicy};
+use super::gap::GapSequence::{Ciura, Knuth, Sedgewick, Shell, Tokuda};
+use super::parallel_sort::{self, ParallelConfig};
+use super::{
+    Sorter, VerifySort, distribution_sort, exchange_sort, insertion_sort, merge_sort,
+    selection_sort, sorting_network, string_sort, tim_sort,
+};
+
+/// ソーティングネットワークで計測する要素数の上限。
+/// ネットワークは入力の長さごとに作り、比較器の数（O(n log^2 n)）に比例する作業領域を使う。
+const NETWORK_MAX_LEN: usize = 1 << 14;
+
+/// ソート関数とその性質をまとめた [`Sorter`] の実装
+struct Entry<T> {
+    name: &'static str,
+    stable: bool,
+    in_place: bool,
+    average: Complexity,
+    worst: Complexity,
+    max_len: Option<usize>,
+    sort: fn(&mut [T]),
+    counted: fn(&mut [T]) -> VerifySort<'_, T>,
+}
+
+impl<T> Sorter<T> for Entry<T> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn is_stable(&self) -> bool {
+        self.stable
+    }
+
+    fn is_in_place(&self) -> bool {
+        self.in_place
+    }
+
+    fn average(&self) -> Complexity {
+        self.average
+    }
+
+    fn worst(&self) -> Complexity {
+        self.worst
+    }
+
+    fn max_len(&self) -> Option<usize> {
+        self.max_len
+    }
+
+    fn sort(&self, arr: &mut [T]) {
+        (self.sort)(arr)
+    }
+
+    fn sort_counted<'a>(&self, arr: &'a mut [T]) -> VerifySort<'a, T> {
+        (self.counted)(arr)
+    }
+}
+
+/// 要素の比較だけでソートする全てのアルゴリズム。並列ソートは [`ParallelConfig::default`] で実行する。
+///
+/// ソーティングネットワークは入力の長さのネットワークを作って適用するため、[`NETWORK_MAX_LEN`] を計測の上限とする。
+pub fn comparison_sorts<T>() -> Vec<Box<dyn Sorter<T>>>
+where
+    T: PartialOrd + Clone + Send + 'static,
+{
+    let entries: Vec<Entry<T>> = vec![
+        Entry {
+            name: "bubble",
+            stable: true,
+            in_place: true,
+            average: Quadratic,
+            worst: Quadratic,
+            max_len: None,
+            sort: exchange_sort::bubble,
+            counted: exchange_sort::bubble_counted,
+        },
+        Entry {
+            name: "shaker",
+            stable: true,
+            in_place: true,
+            average: Quadratic,
+            worst: Quadratic,
+            max_len: None,
+            sort: exchange_sort::shaker,
+            counted: exchange_sort::shaker_counted,
+        },
+        Entry {
+            name: "comb-shell",
+            stable: false,
+            in_place: true,
+            average: Unknown,
+            worst: Quadratic,
+            max_len: None,
+            sort: |arr| exchange_sort::comb(arr, Shell),
+            counted: |arr| exchange_sort::comb_counted(arr, Shell),
+        },
+        Entry {
+            name: "comb-knuth",
+            stable: false,
+            in_place: true,
+            average: Unknown,
+            worst: Quadratic,
+            max_len: None,
+            sort: |arr| exchange_sort::comb(arr, Knuth),
+            counted: |arr| exchange_sort::comb_counted(arr, Knuth),
+        },
+        Entry {
+            name: "comb-sedgewick",
+            stable: false,
+            in_place: true,
+            average: Unknown,
+            worst: Quadratic,
+            max_len: None,
+            sort: |arr| exchange_sort::comb(arr, Sedgewick),
+            counted: |arr| exchange_sort::comb_counted(arr, Sedgewick),
+        },
+        Entry {
+            name: "comb-ciura",
+            stable: false,
+            in_place: true,
+            average: Unknown,
+            worst: Quadratic,
+            max_len: None,
+            sort: |arr| exchange_sort::comb(arr, Ciura),
+            counted: |arr| exchange_sort::comb_counted(arr, Ciura),
+        },
+        Entry {
+            name: "comb-tokuda",
+            stable: false,
+            in_place: true,
+            average: Unknown,
+            worst: Quadratic,
+            max_len: None,
+            sort: |arr| exchange_sort::comb(arr, Tokuda),
+            counted: |arr| exchange_sort::comb_counted(arr, Tokuda),
+        },
+        Entry {
+            name: "quick",
+            stable: false,
+            in_place: true,
+            average: Linearithmic,
+            worst: Linearithmic,
+            max_len: None,
+            sort: exchange_sort::quick,
+            counted: exchange_sort::quick_counted,
+        },
+        Entry {
+            name: "intro",
+            stable: false,
+            in_place: true,
+            average: Linearithmic,
+            worst: Linearithmic,
+            max_len: None,
+            sort: exchange_sort::intro,
+            counted: exchange_sort::intro_counted,
+        },
+        Entry {
+            name: "selection",
+            stable: false,
+            in_place: true,
+            average: Quadratic,
+            worst: Quadratic,
+            max_len: None,
+            sort: selection_sort::selection,
+            counted: selection_sort::selection_counted,
+        },
+        Entry {
+            name: "heap",
+            stable: false,
+            in_place: true,
+            average: Linearithmic,
+            worst: Linearithmic,
+            max_len: None,
+            sort: selection_sort::heap,
+            counted: selection_sort::heap_counted,
+        },
+        Entry {
+            name: "insertion",
+            stable: true,
+            in_place: true,
+            average: Quadratic,
+            worst: Quadratic,
+            max_len: None,
+            sort: insertion_sort::insertion,
+            counted: insertion_sort::insertion_counted,
+        },
+        Entry {
+            name: "shell-shell",
+            stable: false,
+            in_place: true,
+            average: Unknown,
+            worst: Shell.worst(),
+            max_len: None,
+            sort: |arr| insertion_sort::shell(arr, Shell),
+            counted: |arr| insertion_sort::shell_counted(arr, Shell),
+        },
+        Entry {
+            name: "shell-knuth",
+            stable: false,
+            in_place: true,
+            average: Unknown,
+            worst: Knuth.worst(),
+            max_len: None,
+            sort: |arr| insertion_sort::shell(arr, Knuth),
+            counted: |arr| insertion_sort::shell_counted(arr, Knuth),
+        },
+        Entry {
+            name: "shell-sedgewick",
+            stable: false,
+            in_place: true,
+            average: Unknown,
+            worst: Sedgewick.worst(),
+            max_len: None,
+            sort: |arr| insertion_sort::shell(arr, Sedgewick),
+            counted: |arr| insertion_sort::shell_counted(arr, Sedgewick),
+        },
+        Entry {
+            name: "shell-ciura",
+            stable: false,
+            in_place: true,
+            average: Unknown,
+            worst: Ciura.worst(),
+            max_len: None,
+            sort: |arr| insertion_sort::shell(arr, Ciura),
+            counted: |arr| insertion_sort::shell_counted(arr, Ciura),
+        },
+        Entry {
+            name: "shell-tokuda",
+            stable: false,
+            in_place: true,
+            average: Unknown,
+            worst: Tokuda.worst(),
+            max_len: None,
+            sort: |arr| insertion_sort::shell(arr, Tokuda),
+            counted: |arr| insertion_sort::shell_counted(arr, Tokuda),
+        },
+        Entry {
+            name: "merge",
+            stable: true,
+            in_place: false,
+            average: Linearithmic,
+            worst: Linearithmic,
+            max_len: None,
+            sort: merge_sort::merge,
+            counted: merge_sort::merge_counted,
+        },
+        Entry {
+            name: "merge-bottom-up",
+            stable: true,
+            in_place: false,
+            average: Linearithmic,
+            worst: Linearithmic,
+            max_len: None,
+            sort: merge_sort::merge_bottom_up,
+            counted: merge_sort::merge_bottom_up_counted,
+        },
+        Entry {
+            name: "tim",
+            stable: true,
+            in_place: false,
+            average: Linearithmic,
+            worst: Linearithmic,
+            max_len: None,
+            sort: tim_sort::tim,
+            counted: tim_sort::tim_counted,
+        },
+        Entry {
+            name: "parallel-quick",
+            stable: false,
+            in_place: true,
+            average: Linearithmic,
+            worst: Linearithmic,
+            max_len: None,
+            sort: |arr| parallel_sort::quick(arr, ParallelConfig::default()),
+            counted: |arr| parallel_sort::quick_counted(arr, ParallelConfig::default()),
+        },
+        Entry {
+            name: "parallel-merge",
+            stable: true,
+            in_place: false,
+            average: Linearithmic,
+            worst: Linearithmic,
+            max_len: None,
+            sort: |arr| parallel_sort::merge(arr, ParallelConfig::default()),
+            counted: |arr| parallel_sort::merge_counted(arr, ParallelConfig::default()),
+        },
+        Entry {
+            name: "odd-even-merge",
+            stable: false,
+            // 比較器の列を作業領域として持つ
+            in_place: false,
+            average: LinearithmicSquared,
+            worst: LinearithmicSquared,
+            max_len: Some(NETWORK_MAX_LEN),
+            sort: sorting_network::odd_even_merge,
+            counted: sorting_network::odd_even_merge_counted,
+        },
+        Entry {
+            name: "bitonic",
+            stable: false,
+            in_place: false,
+            average: LinearithmicSquared,
+            worst: LinearithmicSquared,
+            max_len: Some(NETWORK_MAX_LEN),
+            sort: sorting_network::bitonic,
+            counted: sorting_network::bitonic_counted,
+        },
+    ];
+
+    entries
+        .into_iter()
+        .map(|entry| Box::new(entry) as Box<dyn Sorter<T>>)
+        .collect()
+}
+
+/// `i32` をソートできる全てのアルゴリズム（比較ソートと分布数え上げソート）。
+pub fn integer_sorts() -> Vec<Box<dyn Sorter<i32>>> {
+    let entries = [
+        Entry {
+            name: "counting",
+            // 値を数えて書き直すため、元の要素の順序という概念がない
+            stable: false,
+            in_place: false,
+            average: LinearWithRange,
+            worst: LinearWithRange,
+            max_len: None,
+            sort: distribution_sort::counting,
+            counted: distribution_sort::counting_counted,
+        },
+        Entry {
+            name: "radix-lsd",
+            stable: true,
+            in_place: false,
+            average: LinearWithDigits,
+            worst: LinearWithDigits,
+            max_len: None,
+            sort: distribution_sort::radix_lsd,
+            counted: distribution_sort::radix_lsd_counted,
+        },
+        Entry {
+            name: "radix-msd",
+            stable: true,
+            in_place: false,
+            average: LinearWithDigits,
+            worst: LinearWithDigits,
+            max_len: None,
+            sort: distribution_sort::radix_msd,
+            counted: distribution_sort::radix_msd_counted,
+        },
+    ];
+
+    let mut sorts = comparison_sorts();
+    sorts.extend(
+        entries
+            .into_iter()
+            .map(|entry| Box::new(entry) as Box<dyn Sorter<i32>>),
+    );
+    sorts
+}
+
+/// [0, 1) の範囲の `f64` をソートできる全てのアルゴリズム（比較ソート、バケットソートと `f64` のソート）。
+///
+/// `f64` のソートは [`float_sort::sort_floats`] を [`NanPolicy::Last`] で実行する。
+/// [`float_sort::sort_floats_with`] はソートの方法を引数に取るため、ここには含めない。
+pub fn unit_interval_sorts() -> Vec<Box<dyn Sorter<f64>>> {
+    let entries = [
+        Entry {
+            name: "bucket",
+            stable: true,
+            in_place: false,
+            average: Linear,
+            worst: Quadratic,
+            max_len: None,
+            sort: distribution_sort::bucket,
+            counted: distribution_sort::bucket_counted,
+        },
+        Entry {
+            name: "float",
+            stable: true,
+            in_place: false,
+            average: Linearithmic,
+            worst: Linearithmic,
+            max_len: None,
+            // NanPolicy::Last はエラーを返さない
+            sort: |arr| float_sort::sort_floats(arr, NanPolicy::Last).unwrap(),
+            counted: |arr| float_sort::sort_floats_counted(arr, NanPolicy::Last).unwrap(),
+        },
+    ];
+
+    let mut sorts = comparison_sorts();
+    sorts.extend(
+        entries
+            .into_iter()
+            .map(|entry| Box::new(entry) as Box<dyn Sorter<f64>>),
+    );
+    sorts
+}
+
+/// `String` をソートできる全てのアルゴリズム（比較ソートと文字列に特化したソート）。
+///
+/// 文字列に特化したソートの計算量の d は、文字列を区別するのに必要な接頭辞の長さ。
+pub fn string_sorts() -> Vec<Box<dyn Sorter<String>>> {
+    let entries = [
+        Entry {
+            name: "multikey-quick",
+            stable: false,
+            in_place: true,
+            average: Linearithmic,
+            worst: Quadratic,
+            max_len: None,
+            sort: string_sort::multikey_quick,
+            counted: string_sort::multikey_quick_counted,
+        },
+        Entry {
+            name: "msd-string",
+            stable: true,
+            in_place: false,
+            average: LinearWithDigits,
+            worst: LinearWithDigits,
+            max_len: None,
+            sort: string_sort::msd_radix,
+            counted: string_sort::msd_radix_counted,
+        },
+        Entry {
+            name: "lcp-merge",
+            stable: true,
+            in_place: false,
+            average: Linearithmic,
+            worst: Linearithmic,
+            max_len: None,
+            sort: string_sort::lcp_merge,
+            counted: string_sort::lcp_merge_counted,
+        },
+    ];
+
+    let mut sorts = comparison_sorts();
+    sorts.extend(
+        entries
+            .into_iter()
+            .map(|entry| Box::new(entry) as Box<dyn Sorter<String>>),
+    );
+    sorts
+}
+
+/// 名前から `i32` のソートアルゴリズムを探す。
+pub fn find(name: &str) -> Option<Box<dyn Sorter<i32>>> {
+    integer_sorts()
+        .into_iter()
+        .find(|sorter| sorter.name() == name)
+}
+
+/// 名前から [0, 1) の範囲の `f64` のソートアルゴリズムを探す。
+pub fn find_float(name: &str) -> Option<Box<dyn Sorter<f64>>> {
+    unit_interval_sorts()
+        .into_iter()
+        .find(|sorter| sorter.name() == name)
+}
+
+/// 名前から `String` のソートアルゴリズムを探す。
+pub fn find_string(name: &str) -> Option<Box<dyn Sorter<String>>> {
+    string_sorts()
+        .into_iter()
+        .find(|sorter| sorter.name() == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sort::make_random_vector;
+
+    #[test]
+    fn test_names_are_unique() {
+        let mut names = integer_sorts()
+            .iter()
+            .map(|sorter| sorter.name())
+            .collect::<Vec<_>>();
+        let len = names.len();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), len);
+    }
+
+    #[test]
+    fn test_find() {
+        let sorter = find("heap").unwrap();
+        assert!(!sorter.is_stable());
+        assert!(sorter.is_in_place());
+        assert_eq!(sorter.worst().to_string(), "O(n log n)");
+        assert!(find("bogo").is_none());
+
+        assert!(find_string("lcp-merge").unwrap().is_stable());
+        assert!(find("lcp-merge").is_none());
+    }
+
+    #[test]
+    fn test_network_sorts_have_size_limit() {
+        for name in ["odd-even-merge", "bitonic"] {
+            let sorter = find(name).unwrap();
+            assert_eq!(sorter.max_len(), Some(NETWORK_MAX_LEN));
+            assert_eq!(sorter.worst().to_string(), "O(n log^2 n)");
+        }
+        assert_eq!(find("heap").unwrap().max_len(), None);
+    }
+
+    #[test]
+    fn test_unit_interval_sorts() {
+        let v = make_random_vector(1000)
+            .into_iter()
+            .map(|x| x as f64 / 10_000.0)
+            .collect::<Vec<_>>();
+
+        for sorter in unit_interval_sorts() {
+            let mut w = v.clone();
+            sorter.sort(&mut w);
+            assert!(w.windows(2).all(|w| w[0] <= w[1]), "{sorter:?}");
+        }
+    }
+}
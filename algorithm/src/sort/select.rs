@@ -0,0 +1,414 @@
+use std::cmp::Ordering;
+
+use super::{Counter, Recorder, VerifySort, exchange_sort, insertion_sort, selection_sort};
+
+/// クイックセレクト (平均: O(n), 最悪: O(n^2))
+/// `arr` を並べ替え、ソートした場合に `k` 番目（0 始まり）に来る要素を `arr[k]` に置く。
+/// `arr[..k]` は `arr[k]` 以下、`arr[k + 1..]` は `arr[k]` 以上の要素になる。
+///
+/// 1. pivot を3点の中央値で選び、pivot より小さい・等しい・大きいの3つに分割する。
+/// 2. k が pivot と等しい要素の範囲にあれば終了。小さい側にあれば小さい側を、大きい側にあれば大きい側だけを同様に分割する。
+///
+/// クイックソートと違い片側しか調べないため、平均 O(n) で済む。
+/// 分割には [`exchange_sort::quick`] の二分割ではなく三分割を使う。
+/// 二分割では pivot と等しい要素が片側に寄り、重複の多い入力で O(n^2) になるが、
+/// 三分割なら等しい要素はまとめて取り除かれるため遅くならない。
+/// 最悪計算量を保証したい場合は [`select_nth_median_of_medians`] を使う。
+///
+/// # Panics
+/// `k >= arr.len()` のときパニックする。
+pub fn select_nth<T: PartialOrd>(arr: &mut [T], k: usize) -> &mut T {
+    select_nth_impl(arr, k, &mut T::lt, &mut ());
+    &mut arr[k]
+}
+
+/// 比較関数 `compare` を用いて [`select_nth`] を行う。
+pub fn select_nth_by<T, F>(arr: &mut [T], k: usize, mut compare: F) -> &mut T
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    select_nth_impl(arr, k, &mut |a, b| compare(a, b) == Ordering::Less, &mut ());
+    &mut arr[k]
+}
+
+/// キー抽出関数 `f` が返すキーの順に [`select_nth`] を行う。
+pub fn select_nth_by_key<T, K, F>(arr: &mut [T], k: usize, mut f: F) -> &mut T
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    select_nth_impl(arr, k, &mut |a, b| f(a).lt(&f(b)), &mut ());
+    &mut arr[k]
+}
+
+/// [`select_nth`] を実行し、比較・交換回数を返す。`sorted` は `k` 番目で分割されただけの状態になる。
+pub fn select_nth_counted<T: PartialOrd>(arr: &mut [T], k: usize) -> VerifySort<'_, T> {
+    super::count(arr, |arr, rec| select_nth_impl(arr, k, &mut T::lt, rec))
+}
+
+fn select_nth_impl<T, F, R>(arr: &mut [T], k: usize, is_less: &mut F, rec: &mut R)
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    assert_index(k, arr.len());
+
+    // arr[lo..hi] に k 番目の要素がある
+    let (mut lo, mut hi) = (0, arr.len());
+    while hi - lo > 1 {
+        let sub = &mut arr[lo..hi];
+        let pivot_index = exchange_sort::choose_pivot(sub, is_less, rec);
+        let (lt, gt) = exchange_sort::partition3(sub, pivot_index, is_less, rec);
+        if k < lo + lt {
+            hi = lo + lt;
+        } else if k >= lo + gt {
+            lo += gt;
+        } else {
+            return;
+        }
+    }
+}
+
+/// 中央値の中央値を用いた選択 (最悪: O(n))
+/// [`select_nth`] と同じ結果になるが、pivot の選び方により最悪でも O(n) で終わる。
+///
+/// 1. 要素を5個ずつのグループに分け、各グループを挿入ソートして中央値を求める。
+/// 2. グループの中央値を先頭に集め、それらの中央値を再帰的に求めて pivot とする。
+///     - pivot より小さい要素、大きい要素はそれぞれ全体の約 3/10 以上あるため、分割後の区間は 7/10 以下になる。
+/// 3. pivot で三分割し、k 番目の要素を含む区間だけを同様に調べる。
+///     - 二分割では pivot と等しい要素が片側に寄って区間が縮まないため、等しい要素を分けておく。
+///
+/// 定数倍が大きいため、多くの入力では [`select_nth`] の方が速い。
+///
+/// # Panics
+/// `k >= arr.len()` のときパニックする。
+pub fn select_nth_median_of_medians<T: PartialOrd>(arr: &mut [T], k: usize) -> &mut T {
+    median_of_medians_impl(arr, k, &mut T::lt, &mut ());
+    &mut arr[k]
+}
+
+/// 比較関数 `compare` を用いて [`select_nth_median_of_medians`] を行う。
+pub fn select_nth_median_of_medians_by<T, F>(arr: &mut [T], k: usize, mut compare: F) -> &mut T
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    median_of_medians_impl(arr, k, &mut |a, b| compare(a, b) == Ordering::Less, &mut ());
+    &mut arr[k]
+}
+
+/// キー抽出関数 `f` が返すキーの順に [`select_nth_median_of_medians`] を行う。
+pub fn select_nth_median_of_medians_by_key<T, K, F>(arr: &mut [T], k: usize, mut f: F) -> &mut T
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    median_of_medians_impl(arr, k, &mut |a, b| f(a).lt(&f(b)), &mut ());
+    &mut arr[k]
+}
+
+/// [`select_nth_median_of_medians`] を実行し、比較・交換回数を返す。
+pub fn select_nth_median_of_medians_counted<T: PartialOrd>(
+    arr: &mut [T],
+    k: usize,
+) -> VerifySort<'_, T> {
+    super::count(arr, |arr, rec| {
+        median_of_medians_impl(arr, k, &mut T::lt, rec)
+    })
+}
+
+fn median_of_medians_impl<T, F, R>(arr: &mut [T], k: usize, is_less: &mut F, rec: &mut R)
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    assert_index(k, arr.len());
+
+    let (mut lo, mut hi) = (0, arr.len());
+    loop {
+        let sub = &mut arr[lo..hi];
+        let n = sub.len();
+        if n <= 5 {
+            insertion_sort::insertion_impl(sub, is_less, rec);
+            return;
+        }
+
+        // g 番目のグループの中央値を sub[g] に移す。g <= 5g なので、未処理のグループは壊さない
+        let groups = n.div_ceil(5);
+        for g in 0..groups {
+            let start = 5 * g;
+            let end = (start + 5).min(n);
+            insertion_sort::insertion_impl(&mut sub[start..end], is_less, rec);
+            let median = start + (end - start) / 2;
+            sub.swap(g, median);
+            rec.swap(&sub[g], &sub[median]);
+        }
+
+        let pivot_index = groups / 2;
+        median_of_medians_impl(&mut sub[..groups], pivot_index, is_less, rec);
+
+        let (lt, gt) = exchange_sort::partition3(sub, pivot_index, is_less, rec);
+        if k < lo + lt {
+            hi = lo + lt;
+        } else if k >= lo + gt {
+            lo += gt;
+        } else {
+            return;
+        }
+    }
+}
+
+/// 部分ソート (O(n log k))
+/// `arr` の中で小さい方から `k` 個の要素を、ソートした状態で `arr[..k]` に置く。`arr[k..]` の順序は不定。
+///
+/// 1. `arr[..k]` を最大ヒープにする。
+/// 2. 残りの要素を順に見て、ヒープの最大値より小さければ最大値と入れ替え、ヒープを再構築する。
+/// 3. 最後に `arr[..k]` をヒープソートする。
+///
+/// `k` が `arr.len()` を超える場合は全体をソートする。
+pub fn partial_sort<T: PartialOrd>(arr: &mut [T], k: usize) {
+    partial_sort_impl(arr, k, &mut T::lt, &mut ());
+}
+
+/// 比較関数 `compare` を用いて [`partial_sort`] を行う。
+pub fn partial_sort_by<T, F>(arr: &mut [T], k: usize, mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    partial_sort_impl(arr, k, &mut |a, b| compare(a, b) == Ordering::Less, &mut ());
+}
+
+/// キー抽出関数 `f` が返すキーの順に [`partial_sort`] を行う。
+pub fn partial_sort_by_key<T, K, F>(arr: &mut [T], k: usize, mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    partial_sort_impl(arr, k, &mut |a, b| f(a).lt(&f(b)), &mut ());
+}
+
+/// [`partial_sort`] を実行し、比較・交換回数を返す。
+pub fn partial_sort_counted<T: PartialOrd>(arr: &mut [T], k: usize) -> VerifySort<'_, T> {
+    super::count(arr, |arr, rec| partial_sort_impl(arr, k, &mut T::lt, rec))
+}
+
+fn partial_sort_impl<T, F, R>(arr: &mut [T], k: usize, is_less: &mut F, rec: &mut R)
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    let k = k.min(arr.len());
+    if k == 0 {
+        return;
+    }
+
+    for i in (0..k / 2).rev() {
+        selection_sort::sift_down(arr, i, k, is_less, rec);
+    }
+
+    for i in k..arr.len() {
+        rec.compare(&arr[i], &arr[0]);
+        if is_less(&arr[i], &arr[0]) {
+            arr.swap(0, i);
+            rec.swap(&arr[0], &arr[i]);
+            selection_sort::sift_down(arr, 0, k, is_less, rec);
+        }
+    }
+
+    selection_sort::heap_impl(&mut arr[..k], is_less, rec);
+}
+
+/// 上位 k 件の抽出 (O(n log k), メモリ O(k))
+/// `iter` から小さい方から `k` 個の要素を取り出し、昇順の `Vec` で返す。
+///
+/// 大きさ `k` の最大ヒープだけを保持し、それより大きい要素は読み捨てるため、
+/// 全体をメモリに載せられないストリームにも使える。
+pub fn top_k<T: PartialOrd>(iter: impl IntoIterator<Item = T>, k: usize) -> Vec<T> {
+    top_k_impl(iter, k, &mut T::lt, &mut ())
+}
+
+/// [`top_k`] を実行し、結果と比較・交換回数を返す。
+pub fn top_k_counted<T: PartialOrd>(
+    iter: impl IntoIterator<Item = T>,
+    k: usize,
+) -> (Vec<T>, Counter) {
+    let mut counter = Counter::default();
+    let top = top_k_impl(iter, k, &mut T::lt, &mut counter);
+    (top, counter)
+}
+
+fn top_k_impl<T, F, R>(
+    iter: impl IntoIterator<Item = T>,
+    k: usize,
+    is_less: &mut F,
+    rec: &mut R,
+) -> Vec<T>
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    let mut heap = Vec::with_capacity(k);
+    if k == 0 {
+        return heap;
+    }
+
+    for x in iter {
+        if heap.len() < k {
+            heap.push(x);
+            if heap.len() == k {
+                for i in (0..k / 2).rev() {
+                    selection_sort::sift_down(&mut heap, i, k, is_less, rec);
+                }
+            }
+            continue;
+        }
+
+        rec.compare(&x, &heap[0]);
+        if is_less(&x, &heap[0]) {
+            heap[0] = x;
+            rec.write(&heap[0]);
+            selection_sort::sift_down(&mut heap, 0, k, is_less, rec);
+        }
+    }
+
+    selection_sort::heap_impl(&mut heap, is_less, rec);
+    heap
+}
+
+fn assert_index(k: usize, len: usize) {
+    assert!(k < len, "index {k} out of range for slice of length {len}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sort::make_random_vector;
+
+    fn inputs() -> Vec<Vec<i32>> {
+        vec![
+            vec![5],
+            make_random_vector(1000),
+            (0..1000).collect(),
+            (0..1000).rev().collect(),
+            (0..1000).map(|x| x % 4).collect(),
+        ]
+    }
+
+    /// `arr[k]` が k 番目の要素で、その前後が正しく分割されているか確認する。
+    fn assert_selected(arr: &[i32], k: usize, expected: &[i32]) {
+        assert_eq!(arr[k], expected[k]);
+        assert!(arr[..k].iter().all(|x| *x <= arr[k]));
+        assert!(arr[k + 1..].iter().all(|x| *x >= arr[k]));
+    }
+
+    #[test]
+    fn test_select_nth() {
+        for v in inputs() {
+            let mut expected = v.clone();
+            expected.sort();
+
+            for k in [0, v.len() / 3, v.len() / 2, v.len() - 1] {
+                let mut w = v.clone();
+                assert_eq!(*select_nth(&mut w, k), expected[k]);
+                assert_selected(&w, k, &expected);
+
+                let mut w = v.clone();
+                assert_eq!(*select_nth_median_of_medians(&mut w, k), expected[k]);
+                assert_selected(&w, k, &expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_select_nth_by() {
+        let mut v = make_random_vector(100);
+        let mut expected = v.clone();
+        expected.sort_by(|a, b| b.cmp(a));
+        assert_eq!(*select_nth_by(&mut v, 10, |a, b| b.cmp(a)), expected[10]);
+        assert_eq!(*select_nth_by_key(&mut v, 10, |x| -x), expected[10]);
+        assert_eq!(
+            *select_nth_median_of_medians_by(&mut v, 10, |a, b| b.cmp(a)),
+            expected[10]
+        );
+        assert_eq!(
+            *select_nth_median_of_medians_by_key(&mut v, 10, |x| -x),
+            expected[10]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_select_nth_out_of_range() {
+        select_nth(&mut [1, 2, 3], 3);
+    }
+
+    #[test]
+    fn test_median_of_medians_is_linear() {
+        // 等しい要素ばかりでも、比較回数は n に比例する程度に収まる
+        let n = 100_000;
+        for mut v in [vec![1; n], make_random_vector(n), (0..n as i32).collect()] {
+            let result = select_nth_median_of_medians_counted(&mut v, n / 2);
+            assert!(result.compare_count < 30 * n);
+        }
+    }
+
+    #[test]
+    fn test_select_nth_duplicates_is_linear() {
+        // 等しい要素ばかりの入力でも、等しい範囲に k が入った時点で終わる
+        let n = 100_000;
+        for mut v in [vec![1; n], (0..n as i32).map(|x| x % 2).collect()] {
+            let result = select_nth_counted(&mut v, n / 2);
+            assert!(result.compare_count < 10 * n);
+        }
+    }
+
+    #[test]
+    fn test_partial_sort() {
+        for v in inputs() {
+            let mut expected = v.clone();
+            expected.sort();
+
+            for k in [0, 1, 10, v.len(), v.len() + 1] {
+                let k_clamped = k.min(v.len());
+                let mut w = v.clone();
+                partial_sort(&mut w, k);
+                assert_eq!(w[..k_clamped], expected[..k_clamped]);
+
+                let mut w = v.clone();
+                partial_sort_by_key(&mut w, k, |x| -x);
+                let mut reversed = expected.clone();
+                reversed.reverse();
+                assert_eq!(w[..k_clamped], reversed[..k_clamped]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_top_k() {
+        let v = make_random_vector(10_000);
+        let mut expected = v.clone();
+        expected.sort();
+
+        assert_eq!(top_k(v.iter().copied(), 0), []);
+        assert_eq!(top_k(v.iter().copied(), 25), expected[..25]);
+        assert_eq!(top_k(v.iter().copied(), 20_000), expected);
+
+        let (top, counter) = top_k_counted(v.iter().copied(), 25);
+        assert_eq!(top, expected[..25]);
+        assert!(counter.compare_count < 10 * v.len());
+    }
+
+    #[test]
+    fn test_select_cheaper_than_sort() {
+        let v = make_random_vector(100_000);
+
+        let mut w = v.clone();
+        let sort = exchange_sort::intro_counted(&mut w);
+        let mut w = v.clone();
+        let select = select_nth_counted(&mut w, v.len() / 2);
+        let mut w = v.clone();
+        let partial = partial_sort_counted(&mut w, 100);
+
+        assert!(select.compare_count * 3 < sort.compare_count);
+        assert!(partial.compare_count * 3 < sort.compare_count);
+    }
+}
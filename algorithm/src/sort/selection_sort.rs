@@ -0,0 +1,148 @@
+use std::cmp::Ordering;
+
+use super::trace::{self, Trace};
+use super::{Recorder, VerifySort};
+
+/// 選択ソート (平均: O(n^2), 最悪: O(n^2))
+/// 0番目から順番に確定させていく。
+///
+/// 1. 0..=n-1 番目の要素について最小値を見つけ、0番目の要素と交換する。
+/// 2. 1..=n-1 番目の要素について最小値を見つけ、1番目の要素と交換する。
+/// 3. これを繰り返す。
+pub fn selection<T: PartialOrd>(arr: &mut [T]) {
+    selection_impl(arr, &mut T::lt, &mut ());
+}
+
+/// 比較関数 `compare` を用いて [`selection`] を行う。
+pub fn selection_by<T, F>(arr: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    selection_impl(arr, &mut |a, b| compare(a, b) == Ordering::Less, &mut ());
+}
+
+/// キー抽出関数 `f` が返すキーの順に [`selection`] を行う。
+pub fn selection_by_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    selection_impl(arr, &mut |a, b| f(a).lt(&f(b)), &mut ());
+}
+
+/// [`selection`] を実行し、比較・交換回数を返す。
+pub fn selection_counted<T: PartialOrd>(arr: &mut [T]) -> VerifySort<'_, T> {
+    super::count(arr, |arr, rec| selection_impl(arr, &mut T::lt, rec))
+}
+
+fn selection_impl<T, F, R>(arr: &mut [T], is_less: &mut F, rec: &mut R)
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    for i in 0..arr.len() {
+        // 最小値が複数ある場合は最初のものを選ぶ
+        let mut min_index = i;
+        for j in i + 1..arr.len() {
+            rec.compare(&arr[j], &arr[min_index]);
+            if is_less(&arr[j], &arr[min_index]) {
+                min_index = j;
+            }
+        }
+
+        if min_index != i {
+            arr.swap(i, min_index);
+            rec.swap(&arr[i], &arr[min_index]);
+        }
+    }
+}
+
+/// ヒープソート (平均: O(n log n), 最悪: O(n log n))
+pub fn heap<T: PartialOrd>(arr: &mut [T]) {
+    heap_impl(arr, &mut T::lt, &mut ());
+}
+
+/// 比較関数 `compare` を用いて [`heap`] を行う。
+pub fn heap_by<T, F>(arr: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    heap_impl(arr, &mut |a, b| compare(a, b) == Ordering::Less, &mut ());
+}
+
+/// キー抽出関数 `f` が返すキーの順に [`heap`] を行う。
+pub fn heap_by_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    heap_impl(arr, &mut |a, b| f(a).lt(&f(b)), &mut ());
+}
+
+/// [`heap`] を実行し、比較・交換回数を返す。
+pub fn heap_counted<T: PartialOrd>(arr: &mut [T]) -> VerifySort<'_, T> {
+    super::count(arr, |arr, rec| heap_impl(arr, &mut T::lt, rec))
+}
+
+/// [`heap`] を実行し、比較・交換の記録を返す。
+pub fn heap_traced<T: PartialOrd + Clone>(arr: &mut [T]) -> Trace<T> {
+    trace::trace(arr, |arr, rec| heap_impl(arr, &mut T::lt, rec))
+}
+
+pub(super) fn heap_impl<T, F, R>(arr: &mut [T], is_less: &mut F, rec: &mut R)
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    let len = arr.len();
+
+    // Build a max heap
+    for i in (0..len / 2).rev() {
+        sift_down(arr, i, len, is_less, rec);
+    }
+
+    // Extract elements from heap one by one
+    for end in (1..len).rev() {
+        arr.swap(0, end);
+        rec.swap(&arr[0], &arr[end]);
+        sift_down(arr, 0, end, is_less, rec);
+    }
+}
+
+/// ヒープの再構築
+pub(super) fn sift_down<T, F, R>(
+    arr: &mut [T],
+    mut root: usize,
+    end: usize,
+    is_less: &mut F,
+    rec: &mut R,
+) where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    loop {
+        let left = 2 * root + 1;
+        let right = 2 * root + 2;
+        let mut largest = root;
+
+        if left < end {
+            rec.compare(&arr[largest], &arr[left]);
+            if is_less(&arr[largest], &arr[left]) {
+                largest = left;
+            }
+        }
+        if right < end {
+            rec.compare(&arr[largest], &arr[right]);
+            if is_less(&arr[largest], &arr[right]) {
+                largest = right;
+            }
+        }
+        if largest == root {
+            break;
+        }
+
+        arr.swap(root, largest);
+        rec.swap(&arr[root], &arr[largest]);
+        root = largest;
+    }
+}
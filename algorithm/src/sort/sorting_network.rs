@@ -0,0 +1,349 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use super::{Recorder, VerifySort};
+
+/// [`Network::verify`] で調べられる入力の長さの上限
+pub const MAX_VERIFY_INPUTS: usize = 24;
+
+/// ソーティングネットワーク
+/// 比較器（2つの位置の要素を比較し、順序が逆なら交換する）を決まった順に並べたもの。
+///
+/// 比較・交換する位置が入力によらないため、分岐予測が外れにくく、
+/// 同じ層の比較器は互いに独立なので並列に実行できる。
+/// 比較器 `(i, j)` は常に `i < j` で、小さい方の要素を `i` に置く。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Network {
+    inputs: usize,
+    comparators: Vec<(usize, usize)>,
+}
+
+impl Network {
+    /// Batcher の奇偶マージソートのネットワーク (比較器の数: O(n log^2 n), 段数: O(log^2 n))
+    ///
+    /// 1. 長さ 1 のソート済みの区間から始め、隣り合う2つの区間を奇偶マージで1つにする。
+    /// 2. 奇偶マージでは、2つの区間の偶数番目同士・奇数番目同士を再帰的にマージし、
+    ///    最後に隣り合う奇数番目と偶数番目を比較する。
+    ///
+    /// n が2のべき乗でなければ、n 以上の2のべき乗のネットワークを作り、
+    /// 範囲外の位置に +∞ があるとみなして、範囲外に触れる比較器を取り除く。
+    pub fn odd_even_merge(inputs: usize) -> Self {
+        let n = inputs.next_power_of_two();
+        let mut comparators = Vec::new();
+
+        // 長さ p のソート済みの区間を2つずつマージする
+        let mut p = 1;
+        while p < n {
+            let mut k = p;
+            while k >= 1 {
+                let mut j = k % p;
+                while j + k < n {
+                    for i in 0..k.min(n - j - k) {
+                        // 同じ長さ 2p の区間に属する組だけを比較する
+                        if (i + j) / (2 * p) == (i + j + k) / (2 * p) {
+                            comparators.push((i + j, i + j + k));
+                        }
+                    }
+                    j += 2 * k;
+                }
+                k /= 2;
+            }
+            p *= 2;
+        }
+
+        Self::truncated(inputs, comparators)
+    }
+
+    /// バイトニックソートのネットワーク (比較器の数: O(n log^2 n), 段数: O(log^2 n))
+    ///
+    /// 1. 長さ p のソート済みの区間を2つ並べ、後ろの区間を逆順に見ると、全体は山型（バイトニック列）になる。
+    /// 2. 区間の先頭と末尾から対称な位置同士を比較すると、前半の全ての要素が後半の全ての要素以下になる。
+    /// 3. 前半と後半はそれぞれバイトニック列なので、距離 p/2, p/4, ..., 1 の比較（ハーフクリーナー）でソートできる。
+    ///
+    /// 後ろの区間を実際に反転する代わりに 2. で対称な位置を比較するので、全ての比較器が小さい方を前に置く。
+    /// そのため、2のべき乗でない n も [`Network::odd_even_merge`] と同じく範囲外の比較器を除いて作れる。
+    pub fn bitonic(inputs: usize) -> Self {
+        let n = inputs.next_power_of_two();
+        let mut comparators = Vec::new();
+
+        let mut p = 1;
+        while p < n {
+            for block in (0..n).step_by(2 * p) {
+                for i in 0..p {
+                    comparators.push((block + i, block + 2 * p - 1 - i));
+                }
+            }
+
+            let mut k = p / 2;
+            while k >= 1 {
+                for block in (0..n).step_by(2 * k) {
+                    for i in 0..k {
+                        comparators.push((block + i, block + i + k));
+                    }
+                }
+                k /= 2;
+            }
+            p *= 2;
+        }
+
+        Self::truncated(inputs, comparators)
+    }
+
+    /// 比較器の列からネットワークを作る。
+    ///
+    /// # Panics
+    /// `i < j < inputs` を満たさない比較器 `(i, j)` があるとパニックする。
+    pub fn from_comparators(inputs: usize, comparators: Vec<(usize, usize)>) -> Self {
+        for &(i, j) in &comparators {
+            assert!(
+                i < j && j < inputs,
+                "invalid comparator ({i}, {j}) for {inputs} inputs"
+            );
+        }
+        Self {
+            inputs,
+            comparators,
+        }
+    }
+
+    /// 位置 `inputs` 以降に触れる比較器を取り除く。
+    fn truncated(inputs: usize, mut comparators: Vec<(usize, usize)>) -> Self {
+        comparators.retain(|&(_, j)| j < inputs);
+        Self {
+            inputs,
+            comparators,
+        }
+    }
+
+    /// ソートできるスライスの長さ
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    /// 実行する順に並べた比較器
+    pub fn comparators(&self) -> &[(usize, usize)] {
+        &self.comparators
+    }
+
+    /// 比較器を、互いに独立に実行できる層に分ける。
+    ///
+    /// 各比較器は、それより前にある同じ位置に触れる比較器の全てより後の層に入る。
+    pub fn layers(&self) -> Vec<Vec<(usize, usize)>> {
+        // depth[i] は位置 i に触れた最後の比較器の層の次の層
+        let mut depth = vec![0; self.inputs];
+        let mut layers: Vec<Vec<(usize, usize)>> = Vec::new();
+
+        for &(i, j) in &self.comparators {
+            let layer = depth[i].max(depth[j]);
+            if layer == layers.len() {
+                layers.push(Vec::new());
+            }
+            layers[layer].push((i, j));
+            depth[i] = layer + 1;
+            depth[j] = layer + 1;
+        }
+        layers
+    }
+
+    /// 層の数（並列に実行したときの段数）
+    pub fn depth(&self) -> usize {
+        self.layers().len()
+    }
+
+    /// ネットワークを `arr` に適用する。
+    ///
+    /// # Panics
+    /// `arr` の長さが [`Network::inputs`] と異なるとパニックする。
+    pub fn apply<T: PartialOrd>(&self, arr: &mut [T]) {
+        self.apply_impl(arr, &mut T::lt, &mut ());
+    }
+
+    /// 比較関数 `compare` を用いて [`Network::apply`] を行う。
+    pub fn apply_by<T, F>(&self, arr: &mut [T], mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.apply_impl(arr, &mut |a, b| compare(a, b) == Ordering::Less, &mut ());
+    }
+
+    /// [`Network::apply`] を実行し、比較・交換回数を返す。比較回数は常に比較器の数になる。
+    pub fn apply_counted<'a, T: PartialOrd>(&self, arr: &'a mut [T]) -> VerifySort<'a, T> {
+        super::count(arr, |arr, rec| self.apply_impl(arr, &mut T::lt, rec))
+    }
+
+    fn apply_impl<T, F, R>(&self, arr: &mut [T], is_less: &mut F, rec: &mut R)
+    where
+        F: FnMut(&T, &T) -> bool,
+        R: Recorder<T>,
+    {
+        assert_eq!(
+            arr.len(),
+            self.inputs,
+            "network for {} inputs applied to a slice of length {}",
+            self.inputs,
+            arr.len()
+        );
+
+        for &(i, j) in &self.comparators {
+            rec.compare(&arr[j], &arr[i]);
+            if is_less(&arr[j], &arr[i]) {
+                arr.swap(i, j);
+                rec.swap(&arr[i], &arr[j]);
+            }
+        }
+    }
+
+    /// 0-1 原理でネットワークが正しくソートすることを確かめる (O(2^n・比較器の数))
+    ///
+    /// 0 と 1 だけからなる全ての入力をソートできるネットワークは、任意の入力をソートできる（0-1 原理）。
+    /// 2^n 通りの入力を、1つの入力を1つの整数のビット列として表して全て試す。
+    ///
+    /// # Errors
+    /// ソートできない 0-1 の入力があれば、その入力を返す。
+    ///
+    /// # Panics
+    /// [`Network::inputs`] が [`MAX_VERIFY_INPUTS`] を超えるとパニックする。
+    pub fn verify(&self) -> Result<(), Vec<u8>> {
+        let n = self.inputs;
+        assert!(
+            n <= MAX_VERIFY_INPUTS,
+            "cannot verify a network with {n} inputs"
+        );
+
+        for input in 0u32..1 << n {
+            let mut bits = input;
+            for &(i, j) in &self.comparators {
+                // 位置 i が 1、位置 j が 0 なら交換する
+                if bits >> i & 1 == 1 && bits >> j & 1 == 0 {
+                    bits ^= 1 << i | 1 << j;
+                }
+            }
+
+            // ソート済みなら、1 は上位のビットに詰まっている
+            let zeros = n - bits.count_ones() as usize;
+            if bits != (1u32 << n).wrapping_sub(1) >> zeros << zeros {
+                return Err((0..n).map(|i| (input >> i & 1) as u8).collect());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 1行に1つの層の比較器を `(i, j)` の形式で並べる。
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for layer in self.layers() {
+            let line = layer
+                .iter()
+                .map(|(i, j)| format!("({i}, {j})"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// 奇偶マージソート (O(n log^2 n))
+/// `arr` の長さの [`Network::odd_even_merge`] を作って適用する。
+/// 同じ長さを何度もソートするなら、ネットワークを作っておいて [`Network::apply`] を使う。
+pub fn odd_even_merge<T: PartialOrd>(arr: &mut [T]) {
+    Network::odd_even_merge(arr.len()).apply(arr);
+}
+
+/// [`odd_even_merge`] を実行し、比較・交換回数を返す。
+pub fn odd_even_merge_counted<T: PartialOrd>(arr: &mut [T]) -> VerifySort<'_, T> {
+    Network::odd_even_merge(arr.len()).apply_counted(arr)
+}
+
+/// バイトニックソート (O(n log^2 n))
+/// `arr` の長さの [`Network::bitonic`] を作って適用する。
+pub fn bitonic<T: PartialOrd>(arr: &mut [T]) {
+    Network::bitonic(arr.len()).apply(arr);
+}
+
+/// [`bitonic`] を実行し、比較・交換回数を返す。
+pub fn bitonic_counted<T: PartialOrd>(arr: &mut [T]) -> VerifySort<'_, T> {
+    Network::bitonic(arr.len()).apply_counted(arr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sort::make_random_vector;
+
+    #[test]
+    fn test_verify_all_sizes() {
+        for n in 0..=16 {
+            for network in [Network::odd_even_merge(n), Network::bitonic(n)] {
+                assert_eq!(network.verify(), Ok(()), "{n}\n{network}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_known_sizes() {
+        // n = 4 の奇偶マージソートは比較器 5 個・3 段
+        let network = Network::odd_even_merge(4);
+        assert_eq!(
+            network.comparators(),
+            [(0, 1), (2, 3), (0, 2), (1, 3), (1, 2)]
+        );
+        assert_eq!(network.depth(), 3);
+        assert_eq!(
+            network.to_string(),
+            "(0, 1) (2, 3)\n(0, 2) (1, 3)\n(1, 2)\n"
+        );
+
+        // n = 2^k では、奇偶マージソートは (k^2 - k + 4) 2^(k-2) - 1 個、
+        // バイトニックソートは (k^2 + k) 2^(k-2) 個、どちらも k(k+1)/2 段
+        assert_eq!(Network::odd_even_merge(16).comparators().len(), 63);
+        assert_eq!(Network::bitonic(16).comparators().len(), 80);
+        assert_eq!(Network::odd_even_merge(16).depth(), 10);
+        assert_eq!(Network::bitonic(16).depth(), 10);
+    }
+
+    #[test]
+    fn test_verify_rejects_broken_network() {
+        // 挿入ソートの最後の比較器を抜いたもの
+        let network = Network::from_comparators(3, vec![(0, 1), (1, 2)]);
+        let counterexample = network.verify().unwrap_err();
+        let mut sorted = counterexample.clone();
+        network.apply(&mut sorted);
+        assert!(sorted.windows(2).any(|w| w[0] > w[1]), "{counterexample:?}");
+    }
+
+    #[test]
+    fn test_apply() {
+        for n in [0, 1, 5, 33, 100] {
+            let v = make_random_vector(n);
+            let mut expected = v.clone();
+            expected.sort();
+
+            let mut w = v.clone();
+            odd_even_merge(&mut w);
+            assert_eq!(w, expected);
+
+            let mut w = v.clone();
+            bitonic(&mut w);
+            assert_eq!(w, expected);
+
+            let network = Network::bitonic(n);
+            let mut w = v.clone();
+            let result = network.apply_counted(&mut w);
+            assert_eq!(result.compare_count, network.comparators().len());
+            assert_eq!(result.sorted, expected);
+
+            let mut w = v.clone();
+            network.apply_by(&mut w, |a, b| b.cmp(a));
+            expected.reverse();
+            assert_eq!(w, expected);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_apply_wrong_length() {
+        Network::odd_even_merge(4).apply(&mut [1, 2, 3]);
+    }
+}
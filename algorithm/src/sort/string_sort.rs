@@ -0,0 +1,480 @@
+use std::cmp::Ordering;
+use std::mem;
+
+use super::{Recorder, VerifySort, insertion_sort};
+
+/// この長さ以下の区間は挿入ソートに切り替える
+const INSERTION_THRESHOLD: usize = 16;
+
+/// `s` の `depth` 番目のバイト。文字列の終端は `None` で、どのバイトよりも小さい。
+fn byte_at(s: &[u8], depth: usize) -> Option<u8> {
+    s.get(depth).copied()
+}
+
+/// `a` と `b` の先頭から `from` バイトが一致しているとして、2つを比較し、共通接頭辞の長さと共に返す。
+fn compare_from(a: &[u8], b: &[u8], from: usize) -> (Ordering, usize) {
+    let common = a[from..]
+        .iter()
+        .zip(&b[from..])
+        .take_while(|(x, y)| x == y)
+        .count();
+    let lcp = from + common;
+    (byte_at(a, lcp).cmp(&byte_at(b, lcp)), lcp)
+}
+
+/// 先頭 `depth` バイトが全て一致している `arr` を、残りのバイト列で挿入ソートする。
+fn insertion_from<T, R>(arr: &mut [T], depth: usize, rec: &mut R)
+where
+    T: AsRef<[u8]>,
+    R: Recorder<T>,
+{
+    insertion_sort::insertion_impl(
+        arr,
+        &mut |a: &T, b: &T| a.as_ref()[depth..] < b.as_ref()[depth..],
+        rec,
+    );
+}
+
+/// 三方向基数クイックソート (平均: O(n log n + D), 最悪: O(n^2 + D), D は全文字列の長さの和)
+/// Bentley–Sedgewick のマルチキークイックソート。文字列を先頭から1バイトずつ見ながらクイックソートする。
+///
+/// 1. 中央の要素の `depth` 番目のバイトをピボットにし、そのバイトが小さい・等しい・大きい区間に三分割する。
+/// 2. 小さい区間と大きい区間は、同じ `depth` で再帰的にソートする。
+/// 3. 等しい区間はそのバイトまで一致しているので、`depth + 1` 番目のバイトで同様にソートする（再帰せずループで続ける）。
+///
+/// 一致した接頭辞を比べ直さないため、共通の接頭辞が長い文字列（パスやキー）で比較ソートより速い。
+/// `String`・`&str`・`Vec<u8>` などバイト列として見られる要素をソートできる。
+/// UTF-8 のバイト列の辞書順は文字列の辞書順と一致する。
+pub fn multikey_quick<T: AsRef<[u8]>>(arr: &mut [T]) {
+    multikey_quick_impl(arr, 0, &mut ());
+}
+
+/// [`multikey_quick`] を実行し、比較・交換回数を返す（比較回数は1バイトの比較の回数）。
+pub fn multikey_quick_counted<T: AsRef<[u8]>>(arr: &mut [T]) -> VerifySort<'_, T> {
+    super::count(arr, |arr, rec| multikey_quick_impl(arr, 0, rec))
+}
+
+fn multikey_quick_impl<T, R>(mut arr: &mut [T], mut depth: usize, rec: &mut R)
+where
+    T: AsRef<[u8]>,
+    R: Recorder<T>,
+{
+    // 等しい区間は再帰せずにループで続ける（共通の接頭辞が長くてもスタックが深くならない）
+    loop {
+        if arr.len() <= INSERTION_THRESHOLD {
+            insertion_from(arr, depth, rec);
+            return;
+        }
+
+        let mid = arr.len() / 2;
+        arr.swap(0, mid);
+        rec.swap(&arr[0], &arr[mid]);
+        let pivot = byte_at(arr[0].as_ref(), depth);
+
+        // arr[..lt] < pivot, arr[lt..i] == pivot, arr[gt..] > pivot
+        let (mut lt, mut i, mut gt) = (0, 1, arr.len());
+        while i < gt {
+            rec.compare(&arr[i], &arr[lt]);
+            match byte_at(arr[i].as_ref(), depth).cmp(&pivot) {
+                Ordering::Less => {
+                    arr.swap(lt, i);
+                    rec.swap(&arr[lt], &arr[i]);
+                    lt += 1;
+                    i += 1;
+                }
+                Ordering::Greater => {
+                    gt -= 1;
+                    arr.swap(i, gt);
+                    rec.swap(&arr[i], &arr[gt]);
+                }
+                Ordering::Equal => i += 1,
+            }
+        }
+
+        let (less, rest) = mem::take(&mut arr).split_at_mut(lt);
+        let (equal, greater) = rest.split_at_mut(gt - lt);
+        multikey_quick_impl(less, depth, rec);
+        multikey_quick_impl(greater, depth, rec);
+        // 終端で一致した文字列は全て等しい
+        if pivot.is_none() {
+            return;
+        }
+        arr = equal;
+        depth += 1;
+    }
+}
+
+/// 文字列の MSD 基数ソート (O(D + n・σ), D は区別に必要な接頭辞の長さの和, σ = 256)
+/// 1バイトを1桁として、先頭のバイトで分けたバケットごとに次のバイトで再帰的にソートする。
+///
+/// 1. `depth` 番目のバイトの値ごとに出現回数を数え、値の順にバケットへ振り分ける。
+///    そのバイトがない（既に終端に達した）文字列は最初のバケットに入れる。
+/// 2. 終端のバケット以外の各バケットについて、`depth + 1` 番目のバイトで同様に振り分ける。
+/// 3. バケットが十分小さくなったら挿入ソートで仕上げる。
+///
+/// 振り分けは出現順を保つので安定。要素はバッファとの交換で移動する。
+pub fn msd_radix<T: AsRef<[u8]> + Clone>(arr: &mut [T]) {
+    msd_radix_impl(arr, &mut ());
+}
+
+/// [`msd_radix`] を実行し、比較・交換回数を返す（比較は挿入ソートに切り替えた部分のみ）。
+pub fn msd_radix_counted<T: AsRef<[u8]> + Clone>(arr: &mut [T]) -> VerifySort<'_, T> {
+    super::count(arr, msd_radix_impl)
+}
+
+fn msd_radix_impl<T, R>(arr: &mut [T], rec: &mut R)
+where
+    T: AsRef<[u8]> + Clone,
+    R: Recorder<T>,
+{
+    if arr.len() <= 1 {
+        return;
+    }
+
+    let mut buf = arr.to_vec();
+    let mut offsets = Vec::new();
+    msd_radix_rec(arr, &mut buf, 0, &mut offsets, rec);
+}
+
+/// バケットの数（終端の分を含む）
+const BUCKETS: usize = 257;
+
+/// 終端を 0、バイト b を b + 1 としたバケットの番号
+fn bucket_of(s: &[u8], depth: usize) -> usize {
+    byte_at(s, depth).map_or(0, |b| b as usize + 1)
+}
+
+/// `offsets` は各段のバケットの境界を積むスタックで、全ての段で同じバッファを使う。
+/// 最後のバケットは再帰せずにループで続けるため、共通の接頭辞が長くてもスタックが深くならない。
+fn msd_radix_rec<T, R>(
+    mut arr: &mut [T],
+    mut buf: &mut [T],
+    mut depth: usize,
+    offsets: &mut Vec<usize>,
+    rec: &mut R,
+) where
+    T: AsRef<[u8]>,
+    R: Recorder<T>,
+{
+    loop {
+        if arr.len() <= INSERTION_THRESHOLD {
+            insertion_from(arr, depth, rec);
+            return;
+        }
+
+        // offsets[base + b + 1] にバケット b の出現回数を数え、累積和でバケット b の開始位置を offsets[base + b] にする
+        let base = offsets.len();
+        offsets.resize(base + BUCKETS + 1, 0);
+        let level = &mut offsets[base..];
+        for s in arr.iter() {
+            level[bucket_of(s.as_ref(), depth) + 1] += 1;
+        }
+        for b in 0..BUCKETS {
+            level[b + 1] += level[b];
+        }
+
+        // 書き込むごとに level[b] を進めるので、振り分け後の level[b] はバケット b の終了位置になる
+        for elem in arr.iter_mut() {
+            let b = bucket_of(elem.as_ref(), depth);
+            mem::swap(elem, &mut buf[level[b]]);
+            rec.swap(&buf[level[b]], elem);
+            level[b] += 1;
+        }
+        for (elem, from) in arr.iter_mut().zip(buf.iter_mut()) {
+            mem::swap(elem, from);
+            rec.swap(elem, from);
+        }
+
+        // バケット 0 は終端に達した文字列で、全て等しい
+        let range = |offsets: &[usize], b: usize| offsets[base + b - 1]..offsets[base + b];
+        let last = (1..BUCKETS).rev().find(|&b| range(offsets, b).len() > 1);
+        for b in 1..last.unwrap_or(1) {
+            let r = range(offsets, b);
+            if r.len() > 1 {
+                msd_radix_rec(&mut arr[r.clone()], &mut buf[r], depth + 1, offsets, rec);
+            }
+        }
+
+        let Some(last) = last else {
+            offsets.truncate(base);
+            return;
+        };
+        let r = range(offsets, last);
+        offsets.truncate(base);
+        arr = &mut mem::take(&mut arr)[r.clone()];
+        buf = &mut mem::take(&mut buf)[r];
+        depth += 1;
+    }
+}
+
+/// LCP マージソート (平均: O(n log n + D), 最悪: O(n log n + D))
+/// 隣り合う要素の最長共通接頭辞（LCP）の長さを覚えておき、マージで一致が分かっている接頭辞を比べ直さない。
+///
+/// 直前に出力した文字列 p と、左右の先頭 a, b との LCP をそれぞれ `ha`, `hb` とすると、
+/// 1. `ha > hb` なら、`hb` 番目のバイトで b は p より大きく a は p と等しいので、文字列を見ずに a を出力する。
+/// 2. `ha < hb` なら、同様に b を出力する。
+/// 3. 等しければ、`ha` 番目のバイトから a と b を比較し、小さい方を出力する。得られた LCP が出力しなかった側の新しい LCP になる。
+///
+/// 出力した側の新しい LCP は、その側の LCP 配列から分かる。
+/// 等しい要素は左側を先に出力するので安定。
+pub fn lcp_merge<T: AsRef<[u8]> + Clone>(src: &mut [T]) {
+    lcp_merge_impl(src, &mut ());
+}
+
+/// [`lcp_merge`] を実行し、比較・交換回数を返す（比較回数は文字列を実際に比較した回数）。
+pub fn lcp_merge_counted<T: AsRef<[u8]> + Clone>(src: &mut [T]) -> VerifySort<'_, T> {
+    super::count(src, lcp_merge_impl)
+}
+
+/// `src` を [`lcp_merge`] でソートし、LCP 配列を返す。
+///
+/// LCP 配列の `i` 番目はソート後の `src[i - 1]` と `src[i]` の最長共通接頭辞の長さで、0 番目は 0。
+pub fn lcp_merge_with_lcp<T: AsRef<[u8]> + Clone>(src: &mut [T]) -> Vec<usize> {
+    let mut lcp = vec![0; src.len()];
+    if src.len() > 1 {
+        let mut buf = src.to_vec();
+        let mut lcp_buf = lcp.clone();
+        lcp_sort(src, &mut buf, &mut lcp, &mut lcp_buf, &mut ());
+    }
+    lcp
+}
+
+fn lcp_merge_impl<T, R>(src: &mut [T], rec: &mut R)
+where
+    T: AsRef<[u8]> + Clone,
+    R: Recorder<T>,
+{
+    if src.len() <= 1 {
+        return;
+    }
+
+    let mut buf = src.to_vec();
+    let mut lcp = vec![0; src.len()];
+    let mut lcp_buf = lcp.clone();
+    lcp_sort(src, &mut buf, &mut lcp, &mut lcp_buf, rec);
+}
+
+/// `src` をソートし、その LCP 配列を `lcp` に書き込む。`buf` と `lcp_buf` は作業領域。
+fn lcp_sort<T, R>(
+    src: &mut [T],
+    buf: &mut [T],
+    lcp: &mut [usize],
+    lcp_buf: &mut [usize],
+    rec: &mut R,
+) where
+    T: AsRef<[u8]>,
+    R: Recorder<T>,
+{
+    let n = src.len();
+    if n <= 1 {
+        lcp.fill(0);
+        return;
+    }
+
+    let mid = n / 2;
+    {
+        let (src_left, src_right) = src.split_at_mut(mid);
+        let (buf_left, buf_right) = buf.split_at_mut(mid);
+        let (lcp_left, lcp_right) = lcp.split_at_mut(mid);
+        let (lcp_buf_left, lcp_buf_right) = lcp_buf.split_at_mut(mid);
+        lcp_sort(src_left, buf_left, lcp_left, lcp_buf_left, rec);
+        lcp_sort(src_right, buf_right, lcp_right, lcp_buf_right, rec);
+    }
+
+    lcp_merge_halves(src, mid, buf, lcp, lcp_buf, rec);
+}
+
+/// ソート済みの `src[..mid]` と `src[mid..]` を、LCP 配列を使って `src` にマージする。
+///
+/// 左半分を `buf` に退避し、`buf[..mid]` と `src[mid..]` の先頭から小さい方を `src` の先頭から詰める。
+/// 書き込み位置は右半分の読み出し位置を追い越さない。
+fn lcp_merge_halves<T, R>(
+    src: &mut [T],
+    mid: usize,
+    buf: &mut [T],
+    lcp: &mut [usize],
+    lcp_buf: &mut [usize],
+    rec: &mut R,
+) where
+    T: AsRef<[u8]>,
+    R: Recorder<T>,
+{
+    let n = src.len();
+    for (elem, tmp) in src[..mid].iter_mut().zip(buf.iter_mut()) {
+        mem::swap(elem, tmp);
+        rec.swap(tmp, elem);
+    }
+    lcp_buf[..mid].copy_from_slice(&lcp[..mid]);
+
+    let (mut i, mut j, mut k) = (0, mid, 0);
+    // 直前に出力した文字列と、左右の先頭との LCP
+    let (mut ha, mut hb) = (0, 0);
+    while i < mid && j < n {
+        let take_left = match ha.cmp(&hb) {
+            Ordering::Greater => true,
+            Ordering::Less => false,
+            Ordering::Equal => {
+                rec.compare(&buf[i], &src[j]);
+                let (order, common) = compare_from(buf[i].as_ref(), src[j].as_ref(), ha);
+                if order == Ordering::Greater {
+                    ha = common;
+                    false
+                } else {
+                    hb = common;
+                    true
+                }
+            }
+        };
+
+        if take_left {
+            mem::swap(&mut src[k], &mut buf[i]);
+            rec.swap(&src[k], &buf[i]);
+            lcp[k] = ha;
+            i += 1;
+            ha = if i < mid { lcp_buf[i] } else { 0 };
+        } else {
+            src.swap(k, j);
+            rec.swap(&src[k], &src[j]);
+            lcp[k] = hb;
+            j += 1;
+            hb = if j < n { lcp[j] } else { 0 };
+        }
+        k += 1;
+    }
+
+    if i < mid {
+        // 左側の残りを詰める
+        lcp[k] = ha;
+        lcp[k + 1..n].copy_from_slice(&lcp_buf[i + 1..mid]);
+        for (elem, tmp) in src[k..].iter_mut().zip(buf[i..mid].iter_mut()) {
+            mem::swap(elem, tmp);
+            rec.swap(elem, tmp);
+        }
+    } else if j < n {
+        // 右側の残りは既に正しい位置にある
+        lcp[j] = hb;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sort::generator::{Distribution, Generator};
+    use crate::sort::merge_sort;
+
+    /// 共通の接頭辞が長いパスのような文字列
+    fn paths() -> Vec<String> {
+        let mut generator = Generator::new(3);
+        let keys = generator.generate::<i32>(Distribution::FewUnique { unique: 50 }, 500);
+        keys.iter()
+            .enumerate()
+            .map(|(i, k)| match i % 4 {
+                0 => format!("/usr/lib/{k}"),
+                1 => format!("/usr/lib/{k}/libc.so"),
+                2 => format!("/usr/local/lib/{k}"),
+                _ => format!("/usr/{}", "ü".repeat(*k as usize % 5)),
+            })
+            .chain(["".to_string(), "/".to_string(), "".to_string()])
+            .collect()
+    }
+
+    #[test]
+    fn test_string_sorts() {
+        let sorts: [fn(&mut [String]); 3] = [multikey_quick, msd_radix, lcp_merge];
+        for sort in sorts {
+            let mut v = paths();
+            let mut expected = v.clone();
+            expected.sort();
+            sort(&mut v);
+            assert_eq!(v, expected);
+
+            let mut empty: [String; 0] = [];
+            sort(&mut empty);
+        }
+    }
+
+    #[test]
+    fn test_long_common_prefix() {
+        // 接頭辞の1バイトごとに再帰すると、スタックが溢れる長さ
+        let prefix = "x".repeat(100_000);
+        let v = (0..100)
+            .map(|i| format!("{prefix}{}", (i * 37) % 100))
+            .chain([prefix.clone(), prefix[1..].to_string()])
+            .collect::<Vec<_>>();
+        let mut expected = v.clone();
+        expected.sort();
+
+        let sorts: [fn(&mut [String]); 3] = [multikey_quick, msd_radix, lcp_merge];
+        for sort in sorts {
+            let mut w = v.clone();
+            sort(&mut w);
+            assert_eq!(w, expected);
+        }
+    }
+
+    #[test]
+    fn test_str_and_bytes() {
+        let mut v = vec![
+            "she", "sells", "sea", "shells", "by", "the", "sea", "shore", "s",
+        ];
+        let mut expected = v.clone();
+        expected.sort();
+        multikey_quick(&mut v);
+        assert_eq!(v, expected);
+
+        let mut v: Vec<&[u8]> = vec![b"\xff", b"\x00\x01", b"", b"\x00"];
+        msd_radix(&mut v);
+        assert_eq!(v, [&b""[..], b"\x00", b"\x00\x01", b"\xff"]);
+    }
+
+    #[test]
+    fn test_stable_sorts() {
+        // 先頭のバイトだけを見る要素で、元の位置が保たれるかを調べる
+        #[derive(Debug, Clone, PartialEq)]
+        struct Tagged(&'static str, usize);
+        impl AsRef<[u8]> for Tagged {
+            fn as_ref(&self) -> &[u8] {
+                self.0.as_bytes()
+            }
+        }
+
+        let v = (0..100)
+            .map(|i| Tagged(["b", "a", "ab", "a"][i % 4], i))
+            .collect::<Vec<_>>();
+        let mut expected = v.clone();
+        expected.sort_by(|x, y| x.0.cmp(y.0));
+
+        let sorts: [fn(&mut [Tagged]); 2] = [msd_radix, lcp_merge];
+        for sort in sorts {
+            let mut w = v.clone();
+            sort(&mut w);
+            assert_eq!(w, expected);
+        }
+    }
+
+    #[test]
+    fn test_lcp_array() {
+        let mut v = ["banana", "band", "ban", "apple", "bandana", "ban"];
+        let lcp = lcp_merge_with_lcp(&mut v);
+        assert_eq!(v, ["apple", "ban", "ban", "banana", "band", "bandana"]);
+        assert_eq!(lcp, [0, 0, 3, 3, 3, 4]);
+
+        let mut v = paths();
+        let lcp = lcp_merge_with_lcp(&mut v);
+        for i in 1..v.len() {
+            let (_, expected) = compare_from(v[i - 1].as_bytes(), v[i].as_bytes(), 0);
+            assert_eq!(lcp[i], expected, "{} {}", v[i - 1], v[i]);
+        }
+    }
+
+    #[test]
+    fn test_counted() {
+        let v = paths();
+        assert!(multikey_quick_counted(&mut v.clone()).compare_count > 0);
+
+        // 分割の仕方は同じなので、LCP を使う分だけ文字列の比較が減る
+        let lcp = lcp_merge_counted(&mut v.clone()).compare_count;
+        let merge = merge_sort::merge_counted(&mut v.clone()).compare_count;
+        assert!(lcp < merge, "{lcp} {merge}");
+    }
+}
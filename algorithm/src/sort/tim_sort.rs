@@ -0,0 +1,661 @@
+use std::cmp::Ordering;
+use std::mem;
+
+use super::{Recorder, VerifySort};
+
+/// この長さ未満の入力はマージせず、二分挿入ソートだけでソートする
+const MIN_MERGE: usize = 64;
+/// ギャロップモードに入るまでに、同じ側から連続して取り出す要素数の初期値
+const MIN_GALLOP: usize = 7;
+
+/// ティムソート (平均: O(n log n), 最悪: O(n log n), 最良: O(n))
+/// 入力中の既にソート済みの部分（ラン）を見つけて、それらを安定にマージしていく適応型マージソート。
+///
+/// 1. 先頭から単調増加（等しいものを含む）または狭義単調減少の区間をランとして切り出す。減少するランは反転する。
+/// 2. ランが minrun（n から決まる 32〜64 程度の値）より短ければ、二分挿入ソートで minrun まで伸ばす。
+/// 3. ランをスタックに積み、スタック上の長さ X, Y, Z（Z が最新）が次の条件を満たすまで隣接するランをマージする。
+///     - X > Y + Z かつ Y > Z
+///     - これにより、マージする2つのランの長さが同程度に保たれ、スタックの深さは O(log n) になる。
+/// 4. 最後に、スタックに残ったランを全てマージする。
+///
+/// マージでは、一方のランから連続して要素を取り出し続ける場合に「ギャロップモード」に切り替え、
+/// 指数探索で取り出す要素数をまとめて求めることで比較回数を減らす。
+pub fn tim<T: PartialOrd + Clone>(src: &mut [T]) {
+    tim_impl(src, &mut T::lt, &mut ());
+}
+
+/// 比較関数 `compare` を用いて [`tim`] を行う。
+pub fn tim_by<T, F>(src: &mut [T], mut compare: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    tim_impl(src, &mut |a, b| compare(a, b) == Ordering::Less, &mut ());
+}
+
+/// キー抽出関数 `f` が返すキーの順に [`tim`] を行う。
+pub fn tim_by_key<T, K, F>(src: &mut [T], mut f: F)
+where
+    T: Clone,
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    tim_impl(src, &mut |a, b| f(a).lt(&f(b)), &mut ());
+}
+
+/// [`tim`] を実行し、比較・交換・代入回数を返す。
+pub fn tim_counted<T: PartialOrd + Clone>(src: &mut [T]) -> VerifySort<'_, T> {
+    super::count(src, |src, rec| tim_impl(src, &mut T::lt, rec))
+}
+
+pub(super) fn tim_impl<T, F, R>(src: &mut [T], is_less: &mut F, rec: &mut R)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    let n = src.len();
+    if n < 2 {
+        return;
+    }
+
+    if n < MIN_MERGE {
+        let run = count_run(src, is_less, rec);
+        binary_insertion(src, run, is_less, rec);
+        return;
+    }
+
+    let min_run = min_run_length(n);
+    let mut state = TimSort {
+        // マージ時に短い方のランを退避する作業領域。短い方のランは n / 2 を超えない
+        buf: src[..n / 2].to_vec(),
+        runs: Vec::new(),
+        min_gallop: MIN_GALLOP,
+    };
+
+    let mut start = 0;
+    while start < n {
+        let mut len = count_run(&mut src[start..], is_less, rec);
+        if len < min_run {
+            let force = min_run.min(n - start);
+            binary_insertion(&mut src[start..start + force], len, is_less, rec);
+            len = force;
+        }
+
+        state.runs.push(Run { start, len });
+        state.merge_collapse(src, is_less, rec);
+        start += len;
+    }
+
+    state.merge_force_collapse(src, is_less, rec);
+}
+
+/// n を2で割り続けて 64 未満にした値。途中で1ビットでも切り捨てたら1を足す。
+///
+/// n / minrun が2のべき乗かそれより少し小さくなるため、最後のマージまで長さの釣り合ったランが続く。
+fn min_run_length(mut n: usize) -> usize {
+    let mut r = 0;
+    while n >= MIN_MERGE {
+        r |= n & 1;
+        n >>= 1;
+    }
+    n + r
+}
+
+/// 先頭から続くランの長さを返す。狭義単調減少のランは反転して単調増加にする。
+///
+/// 減少するランを狭義に限るのは、反転しても等しい要素の順序が入れ替わらない（安定である）ようにするため。
+fn count_run<T, F, R>(arr: &mut [T], is_less: &mut F, rec: &mut R) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    let n = arr.len();
+    if n < 2 {
+        return n;
+    }
+
+    rec.compare(&arr[1], &arr[0]);
+    let descending = is_less(&arr[1], &arr[0]);
+
+    let mut end = 2;
+    while end < n {
+        rec.compare(&arr[end], &arr[end - 1]);
+        let less = is_less(&arr[end], &arr[end - 1]);
+        if less != descending {
+            break;
+        }
+        end += 1;
+    }
+
+    if descending {
+        for i in 0..end / 2 {
+            arr.swap(i, end - 1 - i);
+            rec.swap(&arr[i], &arr[end - 1 - i]);
+        }
+    }
+    end
+}
+
+/// `arr[..sorted]` がソート済みであるとして、残りの要素を二分探索で挿入位置を求めて挿入する。
+fn binary_insertion<T, F, R>(arr: &mut [T], sorted: usize, is_less: &mut F, rec: &mut R)
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    for i in sorted.max(1)..arr.len() {
+        // 等しい要素の後ろに挿入するため、arr[i] より大きい最初の位置を探す
+        let (mut lo, mut hi) = (0, i);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            rec.compare(&arr[i], &arr[mid]);
+            if is_less(&arr[i], &arr[mid]) {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+
+        // 既に正しい位置にあれば動かさない
+        if lo < i {
+            arr[lo..=i].rotate_right(1);
+            for elem in &arr[lo..=i] {
+                rec.write(elem);
+            }
+        }
+    }
+}
+
+/// `pred` が `0..k` で `true`、`k..len` で `false` となる境界 `k` を指数探索で求める。
+///
+/// `from_right` が `false` なら先頭から 1, 2, 4, ... と、`true` なら末尾から同様に間隔を広げて範囲を絞り、
+/// その後に二分探索する。境界が探索を始める端に近いほど比較回数が少ない。
+fn gallop(len: usize, from_right: bool, mut pred: impl FnMut(usize) -> bool) -> usize {
+    // 境界は lo..=hi の範囲にある
+    let (mut lo, mut hi) = (0, len);
+    let mut step = 1;
+
+    if from_right {
+        while step <= len {
+            if pred(len - step) {
+                lo = len - step + 1;
+                break;
+            }
+            hi = len - step;
+            step *= 2;
+        }
+    } else {
+        while step <= len {
+            if !pred(step - 1) {
+                hi = step - 1;
+                break;
+            }
+            lo = step;
+            step *= 2;
+        }
+    }
+
+    while lo < hi {
+        let mid = (lo + hi) / 2;
+        if pred(mid) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+/// `slice` の中で `key` 以下の要素の個数（`key` を等しい要素の後ろに挿入する位置）。
+fn gallop_right<T, F, R>(
+    key: &T,
+    slice: &[T],
+    from_right: bool,
+    is_less: &mut F,
+    rec: &mut R,
+) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    gallop(slice.len(), from_right, |i| {
+        rec.compare(key, &slice[i]);
+        !is_less(key, &slice[i])
+    })
+}
+
+/// `slice` の中で `key` 未満の要素の個数（`key` を等しい要素の前に挿入する位置）。
+fn gallop_left<T, F, R>(
+    key: &T,
+    slice: &[T],
+    from_right: bool,
+    is_less: &mut F,
+    rec: &mut R,
+) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+    R: Recorder<T>,
+{
+    gallop(slice.len(), from_right, |i| {
+        rec.compare(&slice[i], key);
+        is_less(&slice[i], key)
+    })
+}
+
+/// スタックに積まれたラン `src[start..start + len]`
+#[derive(Debug, Clone, Copy)]
+struct Run {
+    start: usize,
+    len: usize,
+}
+
+struct TimSort<T> {
+    buf: Vec<T>,
+    runs: Vec<Run>,
+    /// ギャロップモードに入るしきい値。ギャロップが有効な入力では下げ、無効な入力では上げる
+    min_gallop: usize,
+}
+
+impl<T> TimSort<T> {
+    /// スタック上のランの長さの条件を満たすまで、隣接するランをマージする。
+    fn merge_collapse<F, R>(&mut self, src: &mut [T], is_less: &mut F, rec: &mut R)
+    where
+        F: FnMut(&T, &T) -> bool,
+        R: Recorder<T>,
+    {
+        while self.runs.len() > 1 {
+            let mut n = self.runs.len() - 2;
+            let len = |i: usize| self.runs[i].len;
+
+            if (n > 0 && len(n - 1) <= len(n) + len(n + 1))
+                || (n > 1 && len(n - 2) <= len(n - 1) + len(n))
+            {
+                if len(n - 1) < len(n + 1) {
+                    n -= 1;
+                }
+            } else if len(n) > len(n + 1) {
+                break;
+            }
+            self.merge_at(n, src, is_less, rec);
+        }
+    }
+
+    /// スタックに残ったランを全てマージする。
+    fn merge_force_collapse<F, R>(&mut self, src: &mut [T], is_less: &mut F, rec: &mut R)
+    where
+        F: FnMut(&T, &T) -> bool,
+        R: Recorder<T>,
+    {
+        while self.runs.len() > 1 {
+            let mut n = self.runs.len() - 2;
+            if n > 0 && self.runs[n - 1].len < self.runs[n + 1].len {
+                n -= 1;
+            }
+            self.merge_at(n, src, is_less, rec);
+        }
+    }
+
+    /// スタックの `n` 番目と `n + 1` 番目のランをマージする。
+    fn merge_at<F, R>(&mut self, n: usize, src: &mut [T], is_less: &mut F, rec: &mut R)
+    where
+        F: FnMut(&T, &T) -> bool,
+        R: Recorder<T>,
+    {
+        let Run { start, len: len_a } = self.runs[n];
+        let len_b = self.runs[n + 1].len;
+        self.runs[n].len = len_a + len_b;
+        self.runs.remove(n + 1);
+
+        let run = &mut src[start..start + len_a + len_b];
+
+        // A の先頭のうち B[0] 以下の要素は、既に最終位置にある
+        let k = gallop_right(&run[len_a], &run[..len_a], false, is_less, rec);
+        if k == len_a {
+            return;
+        }
+        let run = &mut run[k..];
+        let len_a = len_a - k;
+
+        // B の末尾のうち A の最後の要素以上の要素も、既に最終位置にある
+        let len_b = gallop_left(&run[len_a - 1], &run[len_a..], true, is_less, rec);
+        if len_b == 0 {
+            return;
+        }
+        let run = &mut run[..len_a + len_b];
+
+        if len_a <= len_b {
+            self.merge_lo(run, len_a, is_less, rec);
+        } else {
+            self.merge_hi(run, len_a, is_less, rec);
+        }
+    }
+
+    /// `run[..mid]`（A）と `run[mid..]`（B）を先頭からマージする。A が B 以下の長さのときに使う。
+    ///
+    /// A をバッファと交換して退避してから、A の元の位置から順に書き込んでいく。
+    /// 書き込み先とまだ読んでいない B の間には、常にバッファから来た値が入っている。
+    fn merge_lo<F, R>(&mut self, run: &mut [T], mid: usize, is_less: &mut F, rec: &mut R)
+    where
+        F: FnMut(&T, &T) -> bool,
+        R: Recorder<T>,
+    {
+        let end = run.len();
+        let buf = &mut self.buf[..mid];
+        for (a, b) in run[..mid].iter_mut().zip(buf.iter_mut()) {
+            mem::swap(a, b);
+            rec.swap(a, b);
+        }
+
+        // i: バッファ上の A の読み込み位置, j: B の読み込み位置, d: 書き込み位置
+        let (mut i, mut j, mut d) = (0, mid, 0);
+        let mut min_gallop = self.min_gallop;
+
+        'outer: while i < mid && j < end {
+            // 1要素ずつ比較するモード
+            let (mut count_a, mut count_b) = (0, 0);
+            while count_a.max(count_b) < min_gallop {
+                rec.compare(&run[j], &buf[i]);
+                if is_less(&run[j], &buf[i]) {
+                    run.swap(d, j);
+                    rec.swap(&run[d], &run[j]);
+                    j += 1;
+                    count_a = 0;
+                    count_b += 1;
+                } else {
+                    mem::swap(&mut run[d], &mut buf[i]);
+                    rec.swap(&run[d], &buf[i]);
+                    i += 1;
+                    count_a += 1;
+                    count_b = 0;
+                }
+                d += 1;
+                if i == mid || j == end {
+                    break 'outer;
+                }
+            }
+
+            // ギャロップモード: 一度に取り出せる要素数を指数探索で求める
+            loop {
+                let k_a = gallop_right(&run[j], &buf[i..], false, is_less, rec);
+                for _ in 0..k_a {
+                    mem::swap(&mut run[d], &mut buf[i]);
+                    rec.swap(&run[d], &buf[i]);
+                    i += 1;
+                    d += 1;
+                }
+                if i == mid {
+                    break 'outer;
+                }
+
+                let k_b = gallop_left(&buf[i], &run[j..], false, is_less, rec);
+                for _ in 0..k_b {
+                    run.swap(d, j);
+                    rec.swap(&run[d], &run[j]);
+                    j += 1;
+                    d += 1;
+                }
+                if j == end {
+                    break 'outer;
+                }
+
+                // B の次の要素は A の先頭以上なので、A の先頭を取り出す
+                mem::swap(&mut run[d], &mut buf[i]);
+                rec.swap(&run[d], &buf[i]);
+                i += 1;
+                d += 1;
+                if i == mid {
+                    break 'outer;
+                }
+
+                min_gallop = min_gallop.saturating_sub(1);
+                if k_a < MIN_GALLOP && k_b < MIN_GALLOP {
+                    break;
+                }
+            }
+            min_gallop += 2;
+        }
+
+        // B が先に尽きたら、残りの A を末尾に書き戻す
+        while i < mid {
+            mem::swap(&mut run[d], &mut buf[i]);
+            rec.swap(&run[d], &buf[i]);
+            i += 1;
+            d += 1;
+        }
+        self.min_gallop = min_gallop.max(1);
+    }
+
+    /// `run[..mid]`（A）と `run[mid..]`（B）を末尾からマージする。B が A より短いときに使う。
+    ///
+    /// B をバッファと交換して退避してから、B の元の位置の末尾から順に書き込んでいく。
+    fn merge_hi<F, R>(&mut self, run: &mut [T], mid: usize, is_less: &mut F, rec: &mut R)
+    where
+        F: FnMut(&T, &T) -> bool,
+        R: Recorder<T>,
+    {
+        let len_b = run.len() - mid;
+        let buf = &mut self.buf[..len_b];
+        for (b, tmp) in run[mid..].iter_mut().zip(buf.iter_mut()) {
+            mem::swap(b, tmp);
+            rec.swap(b, tmp);
+        }
+
+        // 以下のインデックスはいずれも、まだ読み書きしていない範囲の終端（排他的）
+        // i: A の読み込み位置, j: バッファ上の B の読み込み位置, d: 書き込み位置
+        let (mut i, mut j, mut d) = (mid, len_b, run.len());
+        let mut min_gallop = self.min_gallop;
+
+        'outer: while i > 0 && j > 0 {
+            let (mut count_a, mut count_b) = (0, 0);
+            while count_a.max(count_b) < min_gallop {
+                rec.compare(&buf[j - 1], &run[i - 1]);
+                if is_less(&buf[j - 1], &run[i - 1]) {
+                    run.swap(d - 1, i - 1);
+                    rec.swap(&run[d - 1], &run[i - 1]);
+                    i -= 1;
+                    count_a += 1;
+                    count_b = 0;
+                } else {
+                    mem::swap(&mut run[d - 1], &mut buf[j - 1]);
+                    rec.swap(&run[d - 1], &buf[j - 1]);
+                    j -= 1;
+                    count_a = 0;
+                    count_b += 1;
+                }
+                d -= 1;
+                if i == 0 || j == 0 {
+                    break 'outer;
+                }
+            }
+
+            loop {
+                // A の末尾のうち、B の最後の要素より大きいものをまとめて取り出す
+                let k_a = i - gallop_right(&buf[j - 1], &run[..i], true, is_less, rec);
+                for _ in 0..k_a {
+                    run.swap(d - 1, i - 1);
+                    rec.swap(&run[d - 1], &run[i - 1]);
+                    i -= 1;
+                    d -= 1;
+                }
+                if i == 0 {
+                    break 'outer;
+                }
+
+                // B の末尾のうち、A の最後の要素以上のものをまとめて取り出す
+                let k_b = j - gallop_left(&run[i - 1], &buf[..j], true, is_less, rec);
+                for _ in 0..k_b {
+                    mem::swap(&mut run[d - 1], &mut buf[j - 1]);
+                    rec.swap(&run[d - 1], &buf[j - 1]);
+                    j -= 1;
+                    d -= 1;
+                }
+                if j == 0 {
+                    break 'outer;
+                }
+
+                // A の次の要素は B の最後の要素より大きいので、A の末尾を取り出す
+                run.swap(d - 1, i - 1);
+                rec.swap(&run[d - 1], &run[i - 1]);
+                i -= 1;
+                d -= 1;
+                if i == 0 {
+                    break 'outer;
+                }
+
+                min_gallop = min_gallop.saturating_sub(1);
+                if k_a < MIN_GALLOP && k_b < MIN_GALLOP {
+                    break;
+                }
+            }
+            min_gallop += 2;
+        }
+
+        // A が先に尽きたら、残りの B を先頭に書き戻す
+        while j > 0 {
+            mem::swap(&mut run[d - 1], &mut buf[j - 1]);
+            rec.swap(&run[d - 1], &buf[j - 1]);
+            j -= 1;
+            d -= 1;
+        }
+        self.min_gallop = min_gallop.max(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Reverse;
+
+    use super::*;
+    use crate::sort::{count, make_random_vector};
+
+    fn assert_tim_sorts(v: Vec<i32>) {
+        let mut expected = v.clone();
+        expected.sort();
+        let mut w = v;
+        tim(&mut w);
+        assert_eq!(w, expected);
+    }
+
+    #[test]
+    fn test_min_run_length() {
+        assert_eq!(min_run_length(63), 63);
+        assert_eq!(min_run_length(64), 32);
+        assert_eq!(min_run_length(65), 33);
+        assert_eq!(min_run_length(2048), 32);
+        assert_eq!(min_run_length(2049), 33);
+    }
+
+    #[test]
+    fn test_tim_inputs() {
+        for n in [0, 1, 2, 63, 64, 65, 127, 1000, 10_000] {
+            assert_tim_sorts(make_random_vector(n));
+            assert_tim_sorts((0..n as i32).collect());
+            assert_tim_sorts((0..n as i32).rev().collect());
+            assert_tim_sorts((0..n as i32).map(|x| x % 5).collect());
+        }
+    }
+
+    #[test]
+    fn test_tim_runs() {
+        // のこぎり波: 長さの異なる昇順・降順のランが交互に続く
+        let v = (0..20_000)
+            .map(|x: i32| {
+                if x / 700 % 2 == 0 {
+                    x % 700
+                } else {
+                    -(x % 311)
+                }
+            })
+            .collect();
+        assert_tim_sorts(v);
+
+        // 大きな2つのランが重なり合う（ギャロップモードに入る）
+        let v = (0..5000).chain(2500..7500).collect();
+        assert_tim_sorts(v);
+
+        // ほとんどソート済み（末尾に少しだけ追記された）
+        let mut v = (0..10_000).collect::<Vec<_>>();
+        v.extend(make_random_vector(50));
+        assert_tim_sorts(v);
+    }
+
+    #[test]
+    fn test_tim_stable() {
+        for (n, keys) in [(100, 3), (1000, 2), (10_000, 17), (30_000, 1000)] {
+            // 部分的にソート済みの区間を混ぜて、ランのマージとギャロップモードを通す
+            let v = make_random_vector(n)
+                .into_iter()
+                .enumerate()
+                .map(|(i, x)| {
+                    let key = if i % 3000 < 1500 { i as i32 / 50 } else { x };
+                    (key % keys, i)
+                })
+                .collect::<Vec<_>>();
+
+            let mut expected = v.clone();
+            expected.sort_by_key(|&(key, _)| key);
+            let mut w = v.clone();
+            tim_by_key(&mut w, |&(key, _)| key);
+            assert_eq!(w, expected);
+
+            let mut expected = v.clone();
+            expected.sort_by_key(|&(key, _)| Reverse(key));
+            let mut w = v;
+            tim_by(&mut w, |a, b| b.0.cmp(&a.0));
+            assert_eq!(w, expected);
+        }
+    }
+
+    #[test]
+    fn test_binary_insertion_counted_exact() {
+        // ソート済みの入力: i 番目の要素は ceil(log2(i + 1)) 回比較し、要素は動かない。
+        let mut v = (0..8).collect::<Vec<i32>>();
+        let result = count(&mut v, |arr, rec| {
+            binary_insertion(arr, 1, &mut i32::lt, rec)
+        });
+        assert_eq!(result.compare_count, 1 + 1 + 2 + 2 + 2 + 2 + 3);
+        assert_eq!(result.swap_count, 0);
+
+        // 逆順の3要素: 1番目は2要素、2番目は3要素を代入する。
+        let mut v = [3, 2, 1];
+        let result = count(&mut v, |arr, rec| {
+            binary_insertion(arr, 1, &mut i32::lt, rec)
+        });
+        assert_eq!(result.sorted, [1, 2, 3]);
+        assert_eq!(result.compare_count, 3);
+        assert_eq!(result.swap_count, 5);
+
+        // 先頭の2要素だけが逆順: ランを反転する1回の交換の後、残りは挿入で動かない。
+        let mut v = (0..32).collect::<Vec<i32>>();
+        v.swap(0, 1);
+        assert_eq!(tim_counted(&mut v).swap_count, 1);
+    }
+
+    #[test]
+    fn test_tim_sorted_is_linear() {
+        let mut v = (0..10_000).collect::<Vec<i32>>();
+        let result = tim_counted(&mut v);
+        assert_eq!(result.compare_count, 10_000 - 1);
+        assert_eq!(result.swap_count, 0);
+
+        // 狭義単調減少のランは反転するだけでソートできる
+        let mut v = (0..10_000).rev().collect::<Vec<i32>>();
+        let result = tim_counted(&mut v);
+        assert_eq!(result.compare_count, 10_000 - 1);
+        assert_eq!(result.swap_count, 5_000);
+    }
+
+    #[test]
+    fn test_tim_fewer_compares_than_merge() {
+        let mut v = (0..100_000).collect::<Vec<i32>>();
+        for i in (0..v.len()).step_by(1000) {
+            v.swap(i, (i + 500) % 100_000);
+        }
+
+        let mut w = v.clone();
+        let merge = crate::sort::merge_sort::merge_counted(&mut w);
+        let mut w = v;
+        let tim = tim_counted(&mut w);
+        assert!(tim.compare_count * 2 < merge.compare_count);
+    }
+}
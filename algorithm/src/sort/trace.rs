@@ -0,0 +1,366 @@
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::mem;
+use std::thread;
+use std::time::Duration;
+
+use super::{Recorder, exchange_sort, insertion_sort, merge_sort, selection_sort};
+
+/// 操作の記録を返すソート関数
+pub type TracedSort = fn(&mut [i32]) -> Trace<i32>;
+
+/// 操作を記録できるソートの一覧
+pub const ALGORITHMS: [(&str, TracedSort); 6] = [
+    ("bubble", exchange_sort::bubble_traced),
+    ("shaker", exchange_sort::shaker_traced),
+    ("quick", exchange_sort::quick_traced),
+    ("heap", selection_sort::heap_traced),
+    ("insertion", insertion_sort::insertion_traced),
+    ("merge", merge_sort::merge_traced),
+];
+
+/// ソート中の1回の操作。
+///
+/// インデックスはソート対象のスライス上の位置。
+/// マージソートのバッファやピボットの複製など、スライスの外にある要素は `None` になる。
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<T> {
+    /// 2つの要素を比較した
+    Compare { a: Option<usize>, b: Option<usize> },
+    /// スライス内の2つの要素を交換した
+    Swap { a: usize, b: usize },
+    /// スライスの `index` 番目に `value` を代入した（スライスの外の要素との交換を含む）
+    Write { index: usize, value: T },
+}
+
+/// 操作を [`Event`] として記録する [`Recorder`]。
+///
+/// 受け取った要素の参照のアドレスから、スライス上の位置を求める。
+/// 両方ともスライスの外にある要素の交換は、スライスの内容を変えないため記録しない。
+#[derive(Debug)]
+pub struct Tracer<T> {
+    /// スライスの先頭のアドレス
+    start: usize,
+    len: usize,
+    events: Vec<Event<T>>,
+}
+
+impl<T> Tracer<T> {
+    fn new(arr: &[T]) -> Self {
+        Self {
+            start: arr.as_ptr() as usize,
+            len: arr.len(),
+            events: Vec::new(),
+        }
+    }
+
+    /// `elem` がスライスの要素なら、その位置を返す。
+    fn index(&self, elem: &T) -> Option<usize> {
+        let size = mem::size_of::<T>();
+        let offset = (elem as *const T as usize).checked_sub(self.start)?;
+        (size > 0 && offset / size < self.len).then_some(offset / size)
+    }
+}
+
+impl<T: Clone> Recorder<T> for Tracer<T> {
+    fn compare(&mut self, a: &T, b: &T) {
+        let (a, b) = (self.index(a), self.index(b));
+        self.events.push(Event::Compare { a, b });
+    }
+
+    fn swap(&mut self, a: &T, b: &T) {
+        let event = match (self.index(a), self.index(b)) {
+            (Some(i), Some(j)) => Event::Swap { a: i, b: j },
+            (Some(index), None) => Event::Write {
+                index,
+                value: a.clone(),
+            },
+            (None, Some(index)) => Event::Write {
+                index,
+                value: b.clone(),
+            },
+            (None, None) => return,
+        };
+        self.events.push(event);
+    }
+
+    fn write(&mut self, dst: &T) {
+        if let Some(index) = self.index(dst) {
+            self.events.push(Event::Write {
+                index,
+                value: dst.clone(),
+            });
+        }
+    }
+}
+
+/// ソート前のスライスと、ソート中の操作の記録。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace<T> {
+    pub initial: Vec<T>,
+    pub events: Vec<Event<T>>,
+}
+
+/// `sort` を [`Tracer`] 付きで実行し、操作の記録を返す。
+pub fn trace<T: Clone>(arr: &mut [T], sort: impl FnOnce(&mut [T], &mut Tracer<T>)) -> Trace<T> {
+    let initial = arr.to_vec();
+    let mut tracer = Tracer::new(arr);
+    sort(arr, &mut tracer);
+
+    Trace {
+        initial,
+        events: tracer.events,
+    }
+}
+
+impl<T: Clone> Trace<T> {
+    /// 最初のスライスに操作を順に適用し、各操作の後の状態と操作を `f` に渡す。
+    pub fn replay(&self, mut f: impl FnMut(&[T], &Event<T>)) {
+        let mut state = self.initial.clone();
+        for event in &self.events {
+            match event {
+                Event::Compare { .. } => {}
+                Event::Swap { a, b } => state.swap(*a, *b),
+                Event::Write { index, value } => state[*index] = value.clone(),
+            }
+            f(&state, event);
+        }
+    }
+
+    /// 全ての操作を適用した後の状態。
+    pub fn last_state(&self) -> Vec<T> {
+        let mut last = self.initial.clone();
+        self.replay(|state, _| last.clone_from_slice(state));
+        last
+    }
+}
+
+impl<T: Display> Trace<T> {
+    /// 記録を JSON Lines として書き出す。
+    ///
+    /// 1行目はソート前のスライス（`"op": "init"`）、2行目以降は1行に1つの操作。
+    /// 値は `Display` でそのまま書き出すので、数値の要素を想定している。
+    pub fn write_json_lines(&self, w: &mut impl Write) -> io::Result<()> {
+        let values = self
+            .initial
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(w, r#"{{"op": "init", "values": [{values}]}}"#)?;
+
+        let json = |index: &Option<usize>| index.map_or("null".to_string(), |i| i.to_string());
+        for (step, event) in self.events.iter().enumerate() {
+            match event {
+                Event::Compare { a, b } => writeln!(
+                    w,
+                    r#"{{"step": {step}, "op": "compare", "a": {}, "b": {}}}"#,
+                    json(a),
+                    json(b)
+                )?,
+                Event::Swap { a, b } => {
+                    writeln!(w, r#"{{"step": {step}, "op": "swap", "a": {a}, "b": {b}}}"#)?
+                }
+                Event::Write { index, value } => writeln!(
+                    w,
+                    r#"{{"step": {step}, "op": "write", "index": {index}, "value": {value}}}"#
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// アニメーションの設定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnimateConfig {
+    /// 棒グラフの高さ（行数）
+    pub height: usize,
+    /// 1コマの表示時間
+    pub delay: Duration,
+    /// 比較だけの操作をコマにしない
+    pub skip_compares: bool,
+}
+
+impl Default for AnimateConfig {
+    /// 高さ 16 行、1コマ 50 ミリ秒で、比較も表示する。
+    fn default() -> Self {
+        Self {
+            height: 16,
+            delay: Duration::from_millis(50),
+            skip_compares: false,
+        }
+    }
+}
+
+/// 記録を再生し、棒グラフのアニメーションとして `w` に書き出す。
+///
+/// 各コマの前に ANSI エスケープシーケンスで画面を消去する。
+/// 操作した要素の棒は `#` の代わりに `@` で描き、その下に `^` を付ける。
+pub fn animate<T>(trace: &Trace<T>, w: &mut impl Write, config: &AnimateConfig) -> io::Result<()>
+where
+    T: Clone + Into<f64>,
+{
+    let mut result = Ok(());
+    let mut counts = [0usize; 3];
+    let total = trace.events.len();
+
+    trace.replay(|state, event| {
+        let kind = match event {
+            Event::Compare { .. } => 0,
+            Event::Swap { .. } => 1,
+            Event::Write { .. } => 2,
+        };
+        counts[kind] += 1;
+        if result.is_err() || (kind == 0 && config.skip_compares) {
+            return;
+        }
+
+        let step = counts.iter().sum::<usize>();
+        let status = format!(
+            "step {step}/{total}  {}  (compares: {}, swaps: {}, writes: {})",
+            describe(event),
+            counts[0],
+            counts[1],
+            counts[2]
+        );
+        let frame = render_frame(state, event, config.height);
+        result = writeln!(w, "\x1b[H\x1b[2J{frame}{status}").and_then(|()| w.flush());
+        thread::sleep(config.delay);
+    });
+
+    result
+}
+
+fn describe<T>(event: &Event<T>) -> String {
+    let index = |i: &Option<usize>| i.map_or("buffer".to_string(), |i| i.to_string());
+    match event {
+        Event::Compare { a, b } => format!("compare {} and {}", index(a), index(b)),
+        Event::Swap { a, b } => format!("swap {a} and {b}"),
+        Event::Write { index, .. } => format!("write {index}"),
+    }
+}
+
+/// 状態 `state` を高さ `height` 行の棒グラフにし、`event` で操作した要素に印を付ける。
+///
+/// 最小値の棒が高さ 1、最大値の棒が高さ `height` になるように縮尺する。
+fn render_frame<T>(state: &[T], event: &Event<T>, height: usize) -> String
+where
+    T: Clone + Into<f64>,
+{
+    let height = height.max(1);
+    let values = state.iter().cloned().map(Into::into).collect::<Vec<f64>>();
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = (max - min).max(f64::EPSILON);
+    let bars = values
+        .iter()
+        .map(|v| 1 + ((v - min) / range * (height - 1) as f64).round() as usize)
+        .collect::<Vec<_>>();
+
+    let touched = |i: usize| match event {
+        Event::Compare { a, b } => *a == Some(i) || *b == Some(i),
+        Event::Swap { a, b } => *a == i || *b == i,
+        Event::Write { index, .. } => *index == i,
+    };
+
+    let mut frame = String::new();
+    for row in (1..=height).rev() {
+        for (i, &bar) in bars.iter().enumerate() {
+            frame.push(match (bar >= row, touched(i)) {
+                (false, _) => ' ',
+                (true, false) => '#',
+                (true, true) => '@',
+            });
+        }
+        frame.truncate(frame.trim_end().len());
+        frame.push('\n');
+    }
+    for i in 0..bars.len() {
+        frame.push(if touched(i) { '^' } else { ' ' });
+    }
+    frame.truncate(frame.trim_end().len());
+    frame.push('\n');
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay_reaches_sorted() {
+        for (_, sort) in ALGORITHMS {
+            let mut v = vec![5, 3, 8, 1, 9, 2, 7, 3, 6, 4, 0];
+            let trace = sort(&mut v);
+            assert_eq!(trace.initial, [5, 3, 8, 1, 9, 2, 7, 3, 6, 4, 0]);
+            assert_eq!(trace.last_state(), v);
+            assert!(v.windows(2).all(|w| w[0] <= w[1]));
+        }
+    }
+
+    #[test]
+    fn test_events_match_counts() {
+        let v = vec![4, 1, 3, 2];
+        let counted = exchange_sort::bubble_counted(&mut v.clone()).compare_count;
+        let trace = exchange_sort::bubble_traced(&mut v.clone());
+        let compares = trace
+            .events
+            .iter()
+            .filter(|e| matches!(e, Event::Compare { .. }))
+            .count();
+        assert_eq!(compares, counted);
+
+        // [4, 1, 3, 2] → 1回目の走査で 4 が末尾まで移動する
+        assert_eq!(
+            trace.events[..4],
+            [
+                Event::Compare {
+                    a: Some(1),
+                    b: Some(0)
+                },
+                Event::Swap { a: 0, b: 1 },
+                Event::Compare {
+                    a: Some(2),
+                    b: Some(1)
+                },
+                Event::Swap { a: 1, b: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_writes_from_buffer() {
+        let mut v = vec![2, 1];
+        let trace = merge_sort::merge_traced(&mut v);
+        // バッファとの交換は、スライス側への代入として記録される
+        assert!(
+            trace
+                .events
+                .iter()
+                .any(|e| matches!(e, Event::Write { index: 0, value: 1 }))
+        );
+        assert_eq!(trace.last_state(), [1, 2]);
+    }
+
+    #[test]
+    fn test_write_json_lines() {
+        let trace = exchange_sort::bubble_traced(&mut [2, 1]);
+        let mut out = Vec::new();
+        trace.write_json_lines(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                "{\"op\": \"init\", \"values\": [2, 1]}\n",
+                "{\"step\": 0, \"op\": \"compare\", \"a\": 1, \"b\": 0}\n",
+                "{\"step\": 1, \"op\": \"swap\", \"a\": 0, \"b\": 1}\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_frame() {
+        let frame = render_frame(&[1, 3, 2], &Event::Swap { a: 1, b: 2 }, 3);
+        assert_eq!(frame, " @\n @@\n#@@\n ^^\n");
+    }
+}
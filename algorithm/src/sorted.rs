@@ -0,0 +1,300 @@
+//! ソート済みのスライスに対する探索・マージ・集合演算。
+//!
+//! いずれも入力が昇順にソート済みであることを前提とし、確認はしない。
+//! 集合演算は重複を含む入力を多重集合として扱う（C++ の `std::set_union` などと同じ）。
+
+use std::ops::Range;
+
+/// `x` 以上の最初の要素の位置 (O(log n))
+/// `x` を等しい要素の前に挿入する位置で、`x` 以上の要素がなければ `arr.len()`。
+///
+/// 1. 答えが含まれる範囲を `lo..hi` とし、最初は `0..arr.len()` とする。
+/// 2. 中央の要素が `x` 未満なら答えはその右側にあり、そうでなければ中央かその左側にある。
+/// 3. 範囲が空になるまで繰り返す（[`slice::partition_point`] で行う）。
+pub fn lower_bound<T: PartialOrd>(arr: &[T], x: &T) -> usize {
+    arr.partition_point(|elem| elem < x)
+}
+
+/// `x` より大きい最初の要素の位置 (O(log n))
+/// `x` を等しい要素の後ろに挿入する位置で、`x` より大きい要素がなければ `arr.len()`。
+pub fn upper_bound<T: PartialOrd>(arr: &[T], x: &T) -> usize {
+    arr.partition_point(|elem| elem <= x)
+}
+
+/// `x` と等しい要素の範囲 (O(log n))
+/// `x` がなければ、`x` を挿入する位置の空の範囲を返す。
+pub fn equal_range<T: PartialOrd>(arr: &[T], x: &T) -> Range<usize> {
+    let start = lower_bound(arr, x);
+    let end = start + upper_bound(&arr[start..], x);
+    start..end
+}
+
+/// 指数探索（ギャロッピング） (O(log k), k は答えの位置)
+/// [`slice::binary_search`] と同じく、`x` があれば `Ok(位置)`、なければ `Err(挿入する位置)` を返す。
+/// `x` が複数あるときは最初の位置を返す。
+///
+/// 1. 位置 0, 2, 6, 14, ...（2^k - 2 番目）と間隔を2倍に広げながら、`x` 以上の要素が現れるまで進む。
+/// 2. 最後に飛び越えた区間を二分探索する。
+///
+/// 答えが先頭に近いほど速いので、長さの大きく異なるソート済みの列を突き合わせるときに使う。
+pub fn exponential_search<T: PartialOrd>(arr: &[T], x: &T) -> Result<usize, usize> {
+    // 答えは lo..hi の範囲にある
+    let (mut lo, mut hi) = (0, arr.len());
+    let mut step = 1;
+    while lo < hi {
+        let probe = lo + step - 1;
+        if probe < arr.len() && arr[probe] < *x {
+            lo = probe + 1;
+            step *= 2;
+        } else {
+            hi = hi.min(probe);
+            break;
+        }
+    }
+
+    let index = lo + arr[lo..hi].partition_point(|y| y < x);
+    match arr.get(index) {
+        Some(elem) if elem == x => Ok(index),
+        _ => Err(index),
+    }
+}
+
+/// 2つのソート済みのスライスを1つのソート済みの列にマージする (O(n + m))
+/// 等しい要素は `a` の要素を先に並べる（安定）。
+pub fn merge<T: PartialOrd + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    let mut merged = Vec::with_capacity(a.len() + b.len());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if b[j] < a[i] {
+            merged.push(b[j].clone());
+            j += 1;
+        } else {
+            merged.push(a[i].clone());
+            i += 1;
+        }
+    }
+    merged.extend_from_slice(&a[i..]);
+    merged.extend_from_slice(&b[j..]);
+    merged
+}
+
+/// 連続する等しい要素を1つにまとめ、重複のない要素を先頭に詰める (O(n))
+/// 重複のない要素の個数を返す。残りの要素の順序は不定。
+///
+/// `Vec` なら、戻り値で `truncate` すると重複を取り除ける。
+pub fn dedup<T: PartialEq>(arr: &mut [T]) -> usize {
+    if arr.is_empty() {
+        return 0;
+    }
+
+    // arr[..len] は重複のない要素
+    let mut len = 1;
+    for i in 1..arr.len() {
+        if arr[i] != arr[len - 1] {
+            arr.swap(len, i);
+            len += 1;
+        }
+    }
+    len
+}
+
+/// 集合演算で、どちらの列にある要素を残すか
+#[derive(Debug, Clone, Copy)]
+struct Keep {
+    only_a: bool,
+    both: bool,
+    only_b: bool,
+}
+
+/// 2つの列を先頭から突き合わせ、`keep` に従って要素を残す。
+///
+/// 等しい要素は1つずつ組にし、組になった要素は `a` のものを残す。
+fn set_operation<T: PartialOrd + Clone>(a: &[T], b: &[T], keep: Keep) -> Vec<T> {
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] < b[j] {
+            if keep.only_a {
+                result.push(a[i].clone());
+            }
+            i += 1;
+        } else if b[j] < a[i] {
+            if keep.only_b {
+                result.push(b[j].clone());
+            }
+            j += 1;
+        } else {
+            if keep.both {
+                result.push(a[i].clone());
+            }
+            i += 1;
+            j += 1;
+        }
+    }
+    if keep.only_a {
+        result.extend_from_slice(&a[i..]);
+    }
+    if keep.only_b {
+        result.extend_from_slice(&b[j..]);
+    }
+    result
+}
+
+/// 和集合 (O(n + m))
+/// `a` と `b` の少なくとも一方にある要素。重複は多い方の個数だけ残る。
+pub fn union<T: PartialOrd + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    let keep = Keep {
+        only_a: true,
+        both: true,
+        only_b: true,
+    };
+    set_operation(a, b, keep)
+}
+
+/// 積集合 (O(n + m))
+/// `a` と `b` の両方にある要素。重複は少ない方の個数だけ残る。
+pub fn intersection<T: PartialOrd + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    let keep = Keep {
+        only_a: false,
+        both: true,
+        only_b: false,
+    };
+    set_operation(a, b, keep)
+}
+
+/// 差集合 (O(n + m))
+/// `a` にあって `b` にない要素。重複は `a` の個数から `b` の個数を引いた分だけ残る。
+pub fn difference<T: PartialOrd + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    let keep = Keep {
+        only_a: true,
+        both: false,
+        only_b: false,
+    };
+    set_operation(a, b, keep)
+}
+
+/// 対称差 (O(n + m))
+/// `a` と `b` の一方にだけある要素。重複は個数の差の分だけ残る。
+pub fn symmetric_difference<T: PartialOrd + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    let keep = Keep {
+        only_a: true,
+        both: false,
+        only_b: true,
+    };
+    set_operation(a, b, keep)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::sort::generator::{Distribution, Generator};
+
+    /// 値の範囲が狭く、重複を多く含むソート済みの列
+    fn sorted_input(generator: &mut Generator, len: usize, unique: usize) -> Vec<i32> {
+        let mut v = generator.generate::<i32>(Distribution::FewUnique { unique }, len);
+        v.sort();
+        v
+    }
+
+    #[test]
+    fn test_bounds() {
+        let v = [1, 2, 2, 2, 5, 8];
+        assert_eq!(lower_bound(&v, &2), 1);
+        assert_eq!(upper_bound(&v, &2), 4);
+        assert_eq!(equal_range(&v, &2), 1..4);
+        assert_eq!(equal_range(&v, &3), 4..4);
+        assert_eq!(equal_range(&v, &0), 0..0);
+        assert_eq!(equal_range(&v, &9), 6..6);
+        assert_eq!(equal_range::<i32>(&[], &1), 0..0);
+    }
+
+    #[test]
+    fn test_bounds_match_btree_set() {
+        let mut generator = Generator::new(17);
+        for len in [0, 1, 10, 200] {
+            let v = sorted_input(&mut generator, len, 30);
+            let set = v.iter().copied().collect::<BTreeSet<_>>();
+            for x in -1..=31 {
+                let less = v.iter().filter(|&&y| y < x).count();
+                let less_or_equal = v.iter().filter(|&&y| y <= x).count();
+                assert_eq!(lower_bound(&v, &x), less);
+                assert_eq!(upper_bound(&v, &x), less_or_equal);
+                assert_eq!(equal_range(&v, &x), less..less_or_equal);
+
+                let found = exponential_search(&v, &x);
+                if set.contains(&x) {
+                    assert_eq!(found, Ok(less));
+                } else {
+                    assert_eq!(found, Err(less));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_merge_and_dedup() {
+        let merged = merge(&[1, 3, 3, 7], &[2, 3, 8]);
+        assert_eq!(merged, [1, 2, 3, 3, 3, 7, 8]);
+
+        // 等しい要素は a のものが先
+        let a = [1.0, 2.0];
+        let b = [-0.0, 1.0];
+        let merged = merge(&[0.0_f64], &b);
+        assert!(merged[0].is_sign_positive() && merged[1].is_sign_negative());
+        assert_eq!(merge(&a, &b), [-0.0, 1.0, 1.0, 2.0]);
+
+        let mut v = vec![1, 1, 2, 3, 3, 3, 4];
+        let len = dedup(&mut v);
+        v.truncate(len);
+        assert_eq!(v, [1, 2, 3, 4]);
+        assert_eq!(dedup::<i32>(&mut []), 0);
+    }
+
+    #[test]
+    fn test_set_operations_match_btree_set() {
+        let mut generator = Generator::new(23);
+        for (len_a, len_b) in [(0, 0), (0, 10), (50, 5), (100, 100)] {
+            let mut a = sorted_input(&mut generator, len_a, 40);
+            let mut b = sorted_input(&mut generator, len_b, 40);
+
+            let len = dedup(&mut a);
+            a.truncate(len);
+            let len = dedup(&mut b);
+            b.truncate(len);
+            let set_a = a.iter().copied().collect::<BTreeSet<_>>();
+            let set_b = b.iter().copied().collect::<BTreeSet<_>>();
+            assert_eq!(set_a.iter().copied().collect::<Vec<_>>(), a);
+
+            let collect = |set: BTreeSet<&i32>| set.into_iter().copied().collect::<Vec<_>>();
+            assert_eq!(union(&a, &b), collect(set_a.union(&set_b).collect()));
+            assert_eq!(
+                intersection(&a, &b),
+                collect(set_a.intersection(&set_b).collect())
+            );
+            assert_eq!(
+                difference(&a, &b),
+                collect(set_a.difference(&set_b).collect())
+            );
+            assert_eq!(
+                symmetric_difference(&a, &b),
+                collect(set_a.symmetric_difference(&set_b).collect())
+            );
+
+            let mut merged = merge(&a, &b);
+            let len = dedup(&mut merged);
+            assert_eq!(merged[..len], union(&a, &b));
+        }
+    }
+
+    #[test]
+    fn test_multiset_operations() {
+        let a = [1, 1, 1, 2, 3];
+        let b = [1, 2, 2, 4];
+        assert_eq!(union(&a, &b), [1, 1, 1, 2, 2, 3, 4]);
+        assert_eq!(intersection(&a, &b), [1, 2]);
+        assert_eq!(difference(&a, &b), [1, 1, 3]);
+        assert_eq!(symmetric_difference(&a, &b), [1, 1, 2, 3, 4]);
+    }
+}